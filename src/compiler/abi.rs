@@ -0,0 +1,74 @@
+// the System V x86-64 calling convention, which the parser lays out frames and calls by too
+
+use crate::compiler::types::Type;
+
+pub const ARGUMENT_REGISTERS: &[&str] = &["rdi", "rsi", "rdx", "rcx", "r8", "r9"];
+// variadic functions keep rdi to r9 and then xmm0 to xmm7 at the top of their frames
+pub const REGISTER_SAVE_AREA_SIZE: usize = 176;
+
+// classes of the eightbytes of a value in the System V ABI, true for SSE and false for INTEGER
+// None is the MEMORY class of structs passed on the stack and returned through a hidden pointer
+pub fn eightbyte_classes(ty: &Type) -> Option<Vec<bool>> {
+    if !ty.is_aggregate() {
+        return Some(vec![ty.is_floating()]);
+    }
+    if ty.size() > 16 || ty.size() == 0 {
+        return None;
+    }
+
+    let mut classes = vec![true; ty.size().div_ceil(8)];
+    mark_integer_eightbytes(ty, 0, &mut classes);
+    Some(classes)
+}
+
+// an eightbyte is INTEGER if any scalar in it is not floating
+fn mark_integer_eightbytes(ty: &Type, offset: usize, classes: &mut [bool]) {
+    match ty.unqualified() {
+        Type::Struct { members, .. } => {
+            for member in members {
+                mark_integer_eightbytes(&member.ty, offset + member.offset, classes);
+            }
+        }
+        Type::Array { element, length } => {
+            for i in 0..length.unwrap_or(0) {
+                mark_integer_eightbytes(element, offset + i * element.size(), classes);
+            }
+        }
+        ty if !ty.is_floating() => classes[offset / 8] = false,
+        _ => (),
+    }
+}
+
+// registers for arguments of the given types in order, one for each eightbyte
+// arguments left without them are passed on the stack
+pub fn argument_registers<'a>(types: impl Iterator<Item = &'a Type>) -> Vec<Option<Vec<String>>> {
+    let mut registers = Vec::<Option<Vec<String>>>::new();
+    let (mut integer_count, mut floating_count) = (0, 0);
+    for ty in types {
+        let Some(classes) = eightbyte_classes(ty) else {
+            registers.push(None);
+            continue;
+        };
+        let sse_count = classes.iter().filter(|&&is_sse| is_sse).count();
+        // a struct is passed on the stack as a whole unless all its eightbytes fit
+        if integer_count + classes.len() - sse_count > ARGUMENT_REGISTERS.len()
+            || floating_count + sse_count > 8
+        {
+            registers.push(None);
+            continue;
+        }
+        let mut eightbyte_registers = Vec::<String>::new();
+        for is_sse in classes {
+            if is_sse {
+                eightbyte_registers.push(format!("xmm{}", floating_count));
+                floating_count += 1;
+            } else {
+                eightbyte_registers.push(ARGUMENT_REGISTERS[integer_count].to_string());
+                integer_count += 1;
+            }
+        }
+        registers.push(Some(eightbyte_registers));
+    }
+
+    registers
+}
@@ -1,39 +1,448 @@
 pub mod x86_64 {
-    use crate::compiler::ast::Node;
+    use crate::compiler::abi::{
+        argument_registers, eightbyte_classes, ARGUMENT_REGISTERS, REGISTER_SAVE_AREA_SIZE,
+    };
+    use crate::compiler::ast::{FunctionDefinition, Node, Program, Relocation, VariableDefinition};
+    use crate::compiler::types::{BitField, Member, Type};
 
-    const ARGUMENT_REGISTERS: &[&'static str] = &["rdi", "rsi", "rdx", "rcx", "r8", "r9"];
+    // values in registers are kept sign or zero extended to 64 bits according to their type
+    fn load_rax(ty: &Type) -> &'static str {
+        match ty {
+            Type::Char | Type::SignedChar => "movsx rax, BYTE PTR [rax]",
+            Type::Bool | Type::UnsignedChar => "movzx rax, BYTE PTR [rax]",
+            Type::Short => "movsx rax, WORD PTR [rax]",
+            Type::UnsignedShort => "movzx rax, WORD PTR [rax]",
+            Type::Int => "movsxd rax, DWORD PTR [rax]",
+            Type::UnsignedInt | Type::Float => "mov eax, DWORD PTR [rax]",
+            Type::Long
+            | Type::UnsignedLong
+            | Type::LongLong
+            | Type::UnsignedLongLong
+            | Type::Double
+            | Type::Pointer(_) => "mov rax, [rax]",
+            Type::VaList
+            | Type::Array { .. }
+            | Type::VariableArray { .. }
+            | Type::Function { .. } => unreachable!("{:?} decays to a pointer", ty),
+            Type::Struct { .. } => unreachable!("structs are left as their addresses"),
+            Type::IncompleteStruct { .. } => unreachable!("incomplete structs cannot be loaded"),
+            Type::Void => unreachable!("void values cannot be loaded"),
+            Type::Qualified { ty, .. } => load_rax(ty),
+        }
+    }
+
+    fn store_rdi_to_rax(ty: &Type) -> &'static str {
+        match ty.size() {
+            1 => "mov [rax], dil",
+            2 => "mov [rax], di",
+            4 => "mov [rax], edi",
+            _ => "mov [rax], rdi",
+        }
+    }
+
+    fn extend_rax(ty: &Type) -> Option<&'static str> {
+        match ty {
+            Type::Char | Type::SignedChar => Some("movsx rax, al"),
+            Type::Bool | Type::UnsignedChar => Some("movzx rax, al"),
+            Type::Short => Some("movsx rax, ax"),
+            Type::UnsignedShort => Some("movzx rax, ax"),
+            Type::Int => Some("movsxd rax, eax"),
+            Type::UnsignedInt => Some("mov eax, eax"),
+            Type::Long
+            | Type::UnsignedLong
+            | Type::LongLong
+            | Type::UnsignedLongLong
+            | Type::Float
+            | Type::Double
+            | Type::Void
+            | Type::VaList
+            | Type::Pointer(_)
+            | Type::Array { .. }
+            | Type::VariableArray { .. }
+            | Type::Struct { .. }
+            | Type::IncompleteStruct { .. }
+            | Type::Function { .. } => None,
+            Type::Qualified { ty, .. } => extend_rax(ty),
+        }
+    }
+
+    // loads the eightbytes of a struct at the address in base into registers
+    // the last one is loaded byte by byte so as not to read past the end of the struct
+    fn load_eightbytes(ty: &Type, base: &str, registers: &[String]) -> String {
+        let mut asm = String::new();
+        for (i, register) in registers.iter().enumerate() {
+            let size = (ty.size() - 8 * i).min(8);
+            let source = if size == 8 {
+                format!("mov r11, QWORD PTR [{}+{}]\n", base, 8 * i)
+            } else {
+                let mut source = String::from("xor r11d, r11d\n");
+                for j in (0..size).rev() {
+                    source += &format!("shl r11, 8\nmov r11b, BYTE PTR [{}+{}]\n", base, 8 * i + j);
+                }
+                source
+            };
+            asm += &source;
+            if register.starts_with("xmm") {
+                asm += &format!("movq {}, r11\n", register);
+            } else {
+                asm += &format!("mov {}, r11\n", register);
+            }
+        }
+
+        asm
+    }
+
+    // registers that hold the eightbytes of a struct returned in them
+    fn return_registers(classes: &[bool]) -> Vec<String> {
+        let (mut integer_count, mut floating_count) = (0, 0);
+        classes
+            .iter()
+            .map(|&is_sse| {
+                if is_sse {
+                    floating_count += 1;
+                    format!("xmm{}", floating_count - 1)
+                } else {
+                    integer_count += 1;
+                    String::from(["rax", "rdx"][integer_count - 1])
+                }
+            })
+            .collect()
+    }
+
+    // copies size bytes from the address in rsi to the one in rdi
+    fn copy_memory(size: usize) -> String {
+        format!(
+            "mov rcx, {}\n\
+             rep movsb",
+            size
+        )
+    }
+
+    // moves a bit-field in the low bits of a loaded storage unit of type ty to those of register
+    // extending it according to the type
+    fn extract_bit_field(ty: &Type, bit_field: &BitField, register: &str) -> String {
+        format!(
+            "shl {}, {}\n\
+             {} {0}, {}",
+            register,
+            64 - bit_field.bit_offset - bit_field.width,
+            if ty.is_signed() { "sar" } else { "shr" },
+            64 - bit_field.width,
+        )
+    }
+
+    // names of symbols are quoted so that they can be any identifier
+    fn symbol(name: &str) -> String {
+        format!("\"{}\"", name)
+    }
+
+    // gas takes symbols named like registers or operators, such as rip or offset, for those
+    // in intel syntax even when quoted, so operands that refer to symbols are written in at&t syntax
+    fn att_syntax(instructions: &str) -> String {
+        format!(
+            ".att_syntax\n\
+             {}\n\
+             .intel_syntax noprefix",
+            instructions
+        )
+    }
+
+    // functions called by name do not need their address to be evaluated
+    fn direct_callee(callee: &Node) -> Option<&str> {
+        match callee {
+            Node::Address { value } => match value.as_ref() {
+                Node::Function { identifier, .. } => Some(identifier),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    // floating values are kept as their bit patterns in general purpose registers
+    // a float occupies the lower 32 bits and the upper ones are zero
+    fn floating_suffix(ty: &Type) -> &'static str {
+        match ty {
+            Type::Float => "ss",
+            _ => "sd",
+        }
+    }
+
+    fn move_xmm0_to_rax(ty: &Type) -> &'static str {
+        match ty {
+            Type::Float => "movd eax, xmm0",
+            _ => "movq rax, xmm0",
+        }
+    }
+
+    fn convert_rax(from: &Type, to: &Type) -> Option<String> {
+        // any value other than zero converts to 1, including NaN
+        if *to == Type::Bool && from != to {
+            return Some(if from.is_floating() {
+                format!(
+                    "movq xmm0, rax\n\
+                     xorps xmm1, xmm1\n\
+                     ucomi{} xmm0, xmm1\n\
+                     setne al\n\
+                     setp dl\n\
+                     or al, dl\n\
+                     movzb rax, al",
+                    floating_suffix(from)
+                )
+            } else {
+                String::from(
+                    "test rax, rax\n\
+                     setne al\n\
+                     movzb rax, al",
+                )
+            });
+        }
+
+        match (from.is_floating(), to.is_floating()) {
+            (false, false) => extend_rax(to).map(String::from),
+            // cvtsi2sd treats rax as signed, so values above i64::MAX are halved first
+            (false, true) if from.is_unsigned_long() => Some(format!(
+                "test rax, rax\n\
+                 js 1f\n\
+                 cvtsi2{0} xmm0, rax\n\
+                 jmp 2f\n\
+                 1:\n\
+                 mov rdi, rax\n\
+                 and rdi, 1\n\
+                 shr rax\n\
+                 or rax, rdi\n\
+                 cvtsi2{0} xmm0, rax\n\
+                 add{0} xmm0, xmm0\n\
+                 2:\n\
+                 {1}",
+                floating_suffix(to),
+                move_xmm0_to_rax(to),
+            )),
+            (false, true) => Some(format!(
+                "cvtsi2{} xmm0, rax\n\
+                 {}",
+                floating_suffix(to),
+                move_xmm0_to_rax(to),
+            )),
+            (true, false) if *from == Type::Float => Some(format!(
+                "movq xmm0, rax\n\
+                 cvtss2sd xmm0, xmm0\n\
+                 movq rax, xmm0\n\
+                 {}",
+                convert_rax(&Type::Double, to).unwrap(),
+            )),
+            // cvttsd2si produces signed results, so values from 2^63 are shifted down first
+            (true, false) if to.is_unsigned_long() => Some(String::from(
+                "movq xmm0, rax\n\
+                 mov rax, 0x43e0000000000000\n\
+                 movq xmm1, rax\n\
+                 ucomisd xmm0, xmm1\n\
+                 jae 1f\n\
+                 cvttsd2si rax, xmm0\n\
+                 jmp 2f\n\
+                 1:\n\
+                 subsd xmm0, xmm1\n\
+                 cvttsd2si rax, xmm0\n\
+                 btc rax, 63\n\
+                 2:",
+            )),
+            (true, false) => Some(format!(
+                "movq xmm0, rax\n\
+                 cvttsd2si rax, xmm0{}",
+                extend_rax(to)
+                    .map(|extension| format!("\n{}", extension))
+                    .unwrap_or_default(),
+            )),
+            (true, true) if from == to => None,
+            (true, true) => Some(format!(
+                "movq xmm0, rax\n\
+                 cvt{}2{} xmm0, xmm0\n\
+                 {}",
+                floating_suffix(from),
+                floating_suffix(to),
+                move_xmm0_to_rax(to),
+            )),
+        }
+    }
+
+    fn floating_operation(ty: &Type, instruction: &str) -> String {
+        format!(
+            "pop rdi\n\
+             pop rax\n\
+             movq xmm0, rax\n\
+             movq xmm1, rdi\n\
+             {}{} xmm0, xmm1\n\
+             {}\n\
+             push rax",
+            instruction,
+            floating_suffix(ty),
+            move_xmm0_to_rax(ty),
+        )
+    }
+
+    // unordered results of comparisons with NaN are false except for !=
+    fn floating_comparison(ty: &Type, comparison: &str) -> String {
+        format!(
+            "pop rdi\n\
+             pop rax\n\
+             movq xmm0, rax\n\
+             movq xmm1, rdi\n\
+             {}\n\
+             movzb rax, al\n\
+             push rax",
+            match comparison {
+                "<" => format!("ucomi{} xmm1, xmm0\nseta al", floating_suffix(ty)),
+                "<=" => format!("ucomi{} xmm1, xmm0\nsetae al", floating_suffix(ty)),
+                "==" => format!(
+                    "ucomi{} xmm0, xmm1\nsete al\nsetnp dl\nand al, dl",
+                    floating_suffix(ty)
+                ),
+                _ => format!(
+                    "ucomi{} xmm0, xmm1\nsetne al\nsetp dl\nor al, dl",
+                    floating_suffix(ty)
+                ),
+            }
+        )
+    }
 
+    // arithmetic is done in 64 bits and the result is extended again from the width of its type
+    fn wrap_around_rax(ty: &Type) -> String {
+        extend_rax(ty)
+            .map(|extension| format!("{}\n", extension))
+            .unwrap_or_default()
+    }
+
+    // initialization of compound literals runs in the middle of expressions
+    // so its stores are done at the depth of the literal rather than as statements
+    fn initialization_into_asm_string(initialization: &[Node], depth: usize) -> String {
+        initialization
+            .iter()
+            .map(|node| {
+                if node.is_statement() {
+                    node.into_x86_64_string(depth)
+                } else {
+                    node.into_x86_64_string(depth) + "\npop rax"
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    // depth is the number of values pushed by enclosing expressions when the code runs
+    // rsp is aligned to 16 bytes at depth 0, which is where statements run
+    #[allow(clippy::wrong_self_convention)]
     trait IntoX86_64Instructions {
-        fn into_x86_64_string(&self) -> String;
-        fn unref_to_rax(&self) -> String;
+        fn into_x86_64_string(&self, depth: usize) -> String;
+        fn into_x86_64_statement_string(&self) -> String;
+        fn unref_to_rax(&self, depth: usize) -> String;
     }
 
     impl IntoX86_64Instructions for Node {
-        fn into_x86_64_string(&self) -> String {
+        fn into_x86_64_string(&self, depth: usize) -> String {
             match self {
                 Node::Block { statements } => statements
                     .iter()
-                    .map(|node| node.into_x86_64_string() + "\npop rax")
+                    .map(|node| node.into_x86_64_statement_string())
                     .collect::<Vec<_>>()
                     .join("\n"),
                 Node::Integer { value } => format!("push {}", value),
-                Node::LocalVariable {
-                    identifier: _,
-                    offset: _,
+                // push only takes 32-bit immediates
+                Node::Long { value } => format!(
+                    "mov rax, {}\n\
+                     push rax",
+                    value
+                ),
+                Node::Floating { value, ty } => format!(
+                    "mov rax, {}\n\
+                     push rax",
+                    match ty {
+                        Type::Float => (*value as f32).to_bits() as u64,
+                        _ => value.to_bits(),
+                    },
+                ),
+                // void objects are only designated through pointers and never loaded
+                Node::Dereference { value } if *self.ty().unqualified() == Type::Void => {
+                    value.into_x86_64_string(depth)
+                }
+                // aggregates are represented by their addresses
+                Node::LocalVariable { .. }
+                | Node::GlobalVariable { .. }
+                | Node::ThreadLocalVariable { .. }
+                | Node::Dereference { .. }
+                | Node::Member { .. }
+                    if self.ty().is_aggregate() =>
+                {
+                    self.unref_to_rax(depth)
+                }
+                Node::Member {
+                    member:
+                        Member {
+                            ty,
+                            bit_field: Some(bit_field),
+                            ..
+                        },
+                    ..
                 } => format!(
                     "{}\n\
                      pop rax\n\
-                     mov rax, [rax]\n\
+                     {}\n\
+                     {}\n\
                      push rax",
-                    self.unref_to_rax(),
+                    self.unref_to_rax(depth),
+                    load_rax(ty),
+                    extract_bit_field(ty, bit_field, "rax"),
+                ),
+                Node::LocalVariable { ty, .. }
+                | Node::GlobalVariable { ty, .. }
+                | Node::ThreadLocalVariable { ty, .. }
+                | Node::Member {
+                    member: Member { ty, .. },
+                    ..
+                } => format!(
+                    "{}\n\
+                     pop rax\n\
+                     {}\n\
+                     push rax",
+                    self.unref_to_rax(depth),
+                    load_rax(ty),
+                ),
+                Node::CompoundLiteral {
+                    object,
+                    initialization,
+                } => format!(
+                    "{}\n\
+                     {}",
+                    initialization_into_asm_string(initialization, depth),
+                    object.into_x86_64_string(depth),
+                ),
+                Node::VariableArraySizes { sizes, value } => format!(
+                    "{}\n\
+                     {}",
+                    initialization_into_asm_string(sizes, depth),
+                    value.into_x86_64_string(depth),
+                ),
+                // structs returned in memory have already been stored through the hidden pointer
+                // which is their address here and is returned in rax
+                Node::Return { value: None } => String::from(
+                    "mov rsp, rbp\n\
+                     pop rbp\n\
+                     ret",
                 ),
-                Node::Return { value } => format!(
+                Node::Return { value: Some(value) } => format!(
                     "{}\n\
                      pop rax\n\
+                     {}\
                      mov rsp, rbp\n\
                      pop rbp\n\
                      ret",
-                    value.into_x86_64_string(),
+                    value.into_x86_64_string(depth),
+                    match eightbyte_classes(&value.ty()) {
+                        _ if value.ty().is_floating() => String::from("movq xmm0, rax\n"),
+                        Some(classes) if value.ty().is_aggregate() => format!(
+                            "mov rsi, rax\n{}",
+                            load_eightbytes(&value.ty(), "rsi", &return_registers(&classes))
+                        ),
+                        _ => String::new(),
+                    },
                 ),
                 Node::If {
                     condition,
@@ -46,9 +455,9 @@ pub mod x86_64 {
                      je {}\n\
                      {}\n\
                      {}:",
-                    condition.into_x86_64_string(),
+                    condition.into_x86_64_string(depth),
                     end_label,
-                    statement.into_x86_64_string(),
+                    statement.into_x86_64_statement_string(),
                     end_label
                 ),
                 Node::IfElse {
@@ -67,12 +476,12 @@ pub mod x86_64 {
                      {}:\n\
                      {}\n\
                      {}:",
-                    condition.into_x86_64_string(),
+                    condition.into_x86_64_string(depth),
                     else_label,
-                    statement.into_x86_64_string(),
+                    statement.into_x86_64_statement_string(),
                     end_label,
                     else_label,
-                    else_statement.into_x86_64_string(),
+                    else_statement.into_x86_64_statement_string(),
                     end_label
                 ),
                 Node::While {
@@ -90,9 +499,9 @@ pub mod x86_64 {
                      jmp {}\n\
                      {}:",
                     begin_label,
-                    condition.into_x86_64_string(),
+                    condition.into_x86_64_string(depth),
                     end_label,
-                    statement.into_x86_64_string(),
+                    statement.into_x86_64_statement_string(),
                     begin_label,
                     end_label
                 ),
@@ -106,19 +515,18 @@ pub mod x86_64 {
                 } => {
                     let mut result = String::new();
                     if let Some(initialize_statement) = initializer {
-                        result += &(initialize_statement.into_x86_64_string() + "\n");
-                        result += "pop rax\n";
+                        result += &(initialize_statement.into_x86_64_statement_string() + "\n");
                     }
                     result += &format!("{}:\n", begin_label);
                     if let Some(condition) = condition {
-                        result += &(condition.into_x86_64_string() + "\n");
+                        result += &(condition.into_x86_64_string(depth) + "\n");
                         result += "pop rax\n";
                         result += "cmp rax, 0\n";
                         result += &format!("je {}\n", end_label);
                     }
-                    result += &(statement.into_x86_64_string() + "\n");
+                    result += &(statement.into_x86_64_statement_string() + "\n");
                     if let Some(updater) = updater {
-                        result += &(updater.into_x86_64_string() + "\n");
+                        result += &(updater.into_x86_64_string(depth) + "\n");
                         result += "pop rax\n";
                     }
                     result += &format!("jmp {}\n", begin_label);
@@ -126,31 +534,357 @@ pub mod x86_64 {
 
                     result
                 }
+                Node::Switch {
+                    condition,
+                    statement,
+                    cases,
+                    default_label,
+                    end_label,
+                } => {
+                    let mut result = String::new();
+                    result += &(condition.into_x86_64_string(depth) + "\n");
+                    result += "pop rax\n";
+                    // immediates of cmp are 32 bits, sign extended for 64-bit operands
+                    let is_long = condition.ty().size() == 8;
+                    for case in cases {
+                        if is_long {
+                            result += &format!("mov rdi, {}\n", case.value);
+                            result += "cmp rax, rdi\n";
+                        } else {
+                            result += &format!("cmp eax, {}\n", case.value as i32);
+                        }
+                        result += &format!("je {}\n", case.label);
+                    }
+                    result += &format!("jmp {}\n", default_label.as_ref().unwrap_or(end_label));
+                    result += &(statement.into_x86_64_statement_string() + "\n");
+                    result += &format!("{}:", end_label);
+
+                    result
+                }
+                Node::Label { label, statement } => format!(
+                    "{}:\n\
+                     {}",
+                    label,
+                    statement.into_x86_64_statement_string(),
+                ),
+                Node::Break { end_label } => format!("jmp {}", end_label),
+                Node::Goto { label } => format!("jmp {}", label),
+                Node::SaveStackPointer { offset } => format!("mov [rbp-{}], rsp", offset),
+                Node::RestoreStackPointer { offset } => format!("mov rsp, [rbp-{}]", offset),
+                // rsp stays aligned to 16 bytes, which the space is as well
+                // as it starts right below the fixed locals and what has been allocated before
+                Node::Alloca { size } => format!(
+                    "{}\n\
+                     pop rax\n\
+                     add rax, 15\n\
+                     and rax, -16\n\
+                     sub rsp, rax\n\
+                     lea rsi, [rsp+rax]\n\
+                     mov rdi, rsp\n\
+                     {}\n\
+                     lea rax, [rsp+{}]\n\
+                     push rax",
+                    size.into_x86_64_string(depth),
+                    copy_memory(8 * depth),
+                    8 * depth,
+                ),
+                Node::ZeroFill { target } => format!(
+                    "{}\n\
+                     pop rdi\n\
+                     mov rcx, {}\n\
+                     xor eax, eax\n\
+                     rep stosb",
+                    target.unref_to_rax(depth),
+                    target.ty().size(),
+                ),
+                Node::Function { .. } => unreachable!("functions decay to pointers"),
                 Node::FunctionCall {
-                    identifier,
+                    callee,
                     arguments,
+                    ty,
+                    return_buffer,
                 } => {
                     let mut result = String::new();
-                    if arguments.len() > 6 {
-                        panic!("Cannot handle more than 6 arguments.");
+                    // structs returned in memory are written to the buffer passed as a hidden first argument
+                    let classes = eightbyte_classes(ty);
+                    let hidden_argument =
+                        return_buffer
+                            .as_ref()
+                            .filter(|_| classes.is_none())
+                            .map(|buffer| Node::Address {
+                                value: buffer.to_owned(),
+                            });
+                    let arguments = hidden_argument
+                        .iter()
+                        .chain(arguments.iter())
+                        .collect::<Vec<_>>();
+                    let argument_types = arguments.iter().map(|a| a.ty()).collect::<Vec<_>>();
+                    let registers = argument_registers(argument_types.iter());
+                    let (register_arguments, stack_arguments): (Vec<_>, Vec<_>) = arguments
+                        .into_iter()
+                        .zip(registers)
+                        .partition(|(_, registers)| registers.is_some());
+                    // structs on the stack take as many slots as they have eightbytes
+                    let slot_count = |argument: &Node| argument.ty().size().div_ceil(8).max(1);
+                    let stack_slot_count = stack_arguments
+                        .iter()
+                        .map(|(argument, _)| slot_count(argument))
+                        .sum::<usize>();
+
+                    // rsp is realigned with padding so that it is aligned when the arguments
+                    // on the stack are the only values left above it at the call
+                    let padding = (depth + stack_slot_count) % 2;
+                    let stack_size = 8 * (padding + stack_slot_count);
+                    let mut depth = depth + padding;
+                    if padding == 1 {
+                        result += "sub rsp, 8\n";
+                    }
+                    // arguments on the stack are pushed from the last one
+                    // and structs are copied below the values pushed so far
+                    for (argument, _) in stack_arguments.iter().rev() {
+                        result += &(argument.into_x86_64_string(depth) + "\n");
+                        if argument.ty().is_aggregate() {
+                            result += &format!(
+                                "pop rsi\n\
+                                 sub rsp, {}\n\
+                                 mov rdi, rsp\n\
+                                 {}\n",
+                                8 * slot_count(argument),
+                                copy_memory(argument.ty().size()),
+                            );
+                        }
+                        depth += slot_count(argument);
+                    }
+                    // evaluate every argument before filling registers so that nested calls do not clobber them
+                    // structs are left as their addresses until then
+                    for (i, (argument, _)) in register_arguments.iter().enumerate() {
+                        result += &(argument.into_x86_64_string(depth + i) + "\n");
+                    }
+                    let identifier = direct_callee(callee);
+                    if identifier.is_none() {
+                        result +=
+                            &(callee.into_x86_64_string(depth + register_arguments.len()) + "\n");
+                        result += "pop r10\n";
+                    }
+                    for (argument, registers) in register_arguments.iter().rev() {
+                        let registers = registers.as_ref().unwrap();
+                        if argument.ty().is_aggregate() {
+                            result += "pop rax\n";
+                            result += &load_eightbytes(&argument.ty(), "rax", registers);
+                        } else if registers[0].starts_with("xmm") {
+                            result += "pop rax\n";
+                            result += &format!("movq {}, rax\n", registers[0]);
+                        } else {
+                            result += &format!("pop {}\n", registers[0]);
+                        }
                     }
-                    for (i, argument) in arguments.iter().enumerate() {
-                        result += &(argument.into_x86_64_string() + "\n");
-                        result += &format!("pop {}\n", ARGUMENT_REGISTERS[i]);
+                    // variadic callees take the number of vector registers used in al
+                    // it is set for every call since prototypes may be missing
+                    let floating_count = register_arguments
+                        .iter()
+                        .flat_map(|(_, registers)| registers.iter().flatten())
+                        .filter(|register| register.starts_with("xmm"))
+                        .count();
+                    result += &format!("mov eax, {}\n", floating_count);
+                    match identifier {
+                        Some(identifier) => {
+                            result += &(att_syntax(&format!("call {}", symbol(identifier))) + "\n")
+                        }
+                        None => result += "call r10\n",
                     }
-                    result += &format!("call {}", identifier);
+                    if stack_size > 0 {
+                        result += &format!("add rsp, {}\n", stack_size);
+                    }
+                    match (return_buffer, classes) {
+                        // the callee returns the address of the buffer in rax
+                        (Some(_), None) => (),
+                        (Some(buffer), Some(classes)) => {
+                            result += "mov rdi, rax\n";
+                            result += &(buffer.unref_to_rax(depth) + "\n");
+                            result += "pop rax\n";
+                            for (i, register) in return_registers(&classes).iter().enumerate() {
+                                let register = if register == "rax" { "rdi" } else { register };
+                                result += &format!(
+                                    "{} QWORD PTR [rax+{}], {}\n",
+                                    if register.starts_with("xmm") {
+                                        "movq"
+                                    } else {
+                                        "mov"
+                                    },
+                                    8 * i,
+                                    register
+                                );
+                            }
+                        }
+                        (None, _) if ty.is_floating() => {
+                            result += &(move_xmm0_to_rax(ty).to_owned() + "\n")
+                        }
+                        (None, _) => result += &wrap_around_rax(ty),
+                    }
+                    result += "push rax";
 
                     result
                 }
+                Node::VaStart {
+                    ap,
+                    gp_offset,
+                    fp_offset,
+                    overflow_offset,
+                } => format!(
+                    "{}\n\
+                     pop rax\n\
+                     mov DWORD PTR [rax], {}\n\
+                     mov DWORD PTR [rax+4], {}\n\
+                     lea rdi, [rbp+{}]\n\
+                     mov QWORD PTR [rax+8], rdi\n\
+                     lea rdi, [rbp-{}]\n\
+                     mov QWORD PTR [rax+16], rdi\n\
+                     push rax",
+                    ap.into_x86_64_string(depth),
+                    gp_offset,
+                    fp_offset,
+                    16 + overflow_offset,
+                    REGISTER_SAVE_AREA_SIZE,
+                ),
+                // arguments are taken from the register save area until it runs out
+                // and then from the ones passed on the stack
+                Node::VaArg { ap, ty } => format!(
+                    "{}\n\
+                     pop rax\n\
+                     mov edi, DWORD PTR [rax+{}]\n\
+                     cmp edi, {}\n\
+                     jae 1f\n\
+                     lea edx, [rdi+{}]\n\
+                     mov DWORD PTR [rax+{1}], edx\n\
+                     add rdi, QWORD PTR [rax+16]\n\
+                     jmp 2f\n\
+                     1:\n\
+                     mov rdi, QWORD PTR [rax+8]\n\
+                     lea rdx, [rdi+8]\n\
+                     mov QWORD PTR [rax+8], rdx\n\
+                     2:\n\
+                     mov rax, rdi\n\
+                     {}\n\
+                     push rax",
+                    ap.into_x86_64_string(depth),
+                    if ty.is_floating() { 4 } else { 0 },
+                    if ty.is_floating() {
+                        REGISTER_SAVE_AREA_SIZE
+                    } else {
+                        8 * ARGUMENT_REGISTERS.len()
+                    },
+                    if ty.is_floating() { 16 } else { 8 },
+                    load_rax(ty),
+                ),
+                // comparisons already produce 0 or 1
+                Node::Cast {
+                    value,
+                    ty: Type::Bool,
+                } if matches!(
+                    value.as_ref(),
+                    Node::OperatorLt { .. }
+                        | Node::OperatorLtEq { .. }
+                        | Node::OperatorEq { .. }
+                        | Node::OperatorNe { .. }
+                ) =>
+                {
+                    value.into_x86_64_string(depth)
+                }
+                Node::Cast { value, ty } => match convert_rax(&value.ty(), ty) {
+                    Some(extension) => format!(
+                        "{}\n\
+                         pop rax\n\
+                         {}\n\
+                         push rax",
+                        value.into_x86_64_string(depth),
+                        extension,
+                    ),
+                    None => value.into_x86_64_string(depth),
+                },
+                Node::Address { value } => value.unref_to_rax(depth),
+                Node::Dereference { value } => format!(
+                    "{}\n\
+                     pop rax\n\
+                     {}\n\
+                     push rax",
+                    value.into_x86_64_string(depth),
+                    load_rax(&self.ty()),
+                ),
+                Node::OperatorAdd { lhs, rhs } if self.ty().is_floating() => format!(
+                    "{}\n\
+                     {}\n\
+                     {}",
+                    lhs.into_x86_64_string(depth),
+                    rhs.into_x86_64_string(depth + 1),
+                    floating_operation(&self.ty(), "add"),
+                ),
+                Node::OperatorSub { lhs, rhs } if self.ty().is_floating() => format!(
+                    "{}\n\
+                     {}\n\
+                     {}",
+                    lhs.into_x86_64_string(depth),
+                    rhs.into_x86_64_string(depth + 1),
+                    floating_operation(&self.ty(), "sub"),
+                ),
+                Node::OperatorMul { lhs, rhs } if self.ty().is_floating() => format!(
+                    "{}\n\
+                     {}\n\
+                     {}",
+                    lhs.into_x86_64_string(depth),
+                    rhs.into_x86_64_string(depth + 1),
+                    floating_operation(&self.ty(), "mul"),
+                ),
+                Node::OperatorDiv { lhs, rhs } if self.ty().is_floating() => format!(
+                    "{}\n\
+                     {}\n\
+                     {}",
+                    lhs.into_x86_64_string(depth),
+                    rhs.into_x86_64_string(depth + 1),
+                    floating_operation(&self.ty(), "div"),
+                ),
+                Node::OperatorLt { lhs, rhs } if lhs.ty().is_floating() => format!(
+                    "{}\n\
+                     {}\n\
+                     {}",
+                    lhs.into_x86_64_string(depth),
+                    rhs.into_x86_64_string(depth + 1),
+                    floating_comparison(&lhs.ty(), "<"),
+                ),
+                Node::OperatorLtEq { lhs, rhs } if lhs.ty().is_floating() => format!(
+                    "{}\n\
+                     {}\n\
+                     {}",
+                    lhs.into_x86_64_string(depth),
+                    rhs.into_x86_64_string(depth + 1),
+                    floating_comparison(&lhs.ty(), "<="),
+                ),
+                Node::OperatorEq { lhs, rhs } if lhs.ty().is_floating() => format!(
+                    "{}\n\
+                     {}\n\
+                     {}",
+                    lhs.into_x86_64_string(depth),
+                    rhs.into_x86_64_string(depth + 1),
+                    floating_comparison(&lhs.ty(), "=="),
+                ),
+                Node::OperatorNe { lhs, rhs } if lhs.ty().is_floating() => format!(
+                    "{}\n\
+                     {}\n\
+                     {}",
+                    lhs.into_x86_64_string(depth),
+                    rhs.into_x86_64_string(depth + 1),
+                    floating_comparison(&lhs.ty(), "!="),
+                ),
                 Node::OperatorAdd { lhs, rhs } => format!(
                     "{}\n\
                      {}\n\
                      pop rdi\n\
                      pop rax\n\
                      add rax, rdi\n\
+                     {}\
                      push rax",
-                    lhs.into_x86_64_string(),
-                    rhs.into_x86_64_string(),
+                    lhs.into_x86_64_string(depth),
+                    rhs.into_x86_64_string(depth + 1),
+                    wrap_around_rax(&self.ty()),
                 ),
                 Node::OperatorSub { lhs, rhs } => format!(
                     "{}\n\
@@ -158,9 +892,11 @@ pub mod x86_64 {
                      pop rdi\n\
                      pop rax\n\
                      sub rax, rdi\n\
+                     {}\
                      push rax",
-                    lhs.into_x86_64_string(),
-                    rhs.into_x86_64_string(),
+                    lhs.into_x86_64_string(depth),
+                    rhs.into_x86_64_string(depth + 1),
+                    wrap_around_rax(&self.ty()),
                 ),
                 Node::OperatorMul { lhs, rhs } => format!(
                     "{}\n\
@@ -168,20 +904,30 @@ pub mod x86_64 {
                      pop rdi\n\
                      pop rax\n\
                      imul rax, rdi\n\
+                     {}\
                      push rax",
-                    lhs.into_x86_64_string(),
-                    rhs.into_x86_64_string(),
+                    lhs.into_x86_64_string(depth),
+                    rhs.into_x86_64_string(depth + 1),
+                    wrap_around_rax(&self.ty()),
                 ),
                 Node::OperatorDiv { lhs, rhs } => format!(
                     "{}\n\
                      {}\n\
                      pop rdi\n\
                      pop rax\n\
-                     cqo\n\
-                     idiv rax, rdi\n\
+                     {}\n\
+                     {}\
                      push rax",
-                    lhs.into_x86_64_string(),
-                    rhs.into_x86_64_string(),
+                    lhs.into_x86_64_string(depth),
+                    rhs.into_x86_64_string(depth + 1),
+                    if self.ty().is_signed() {
+                        "cqo\n\
+                         idiv rax, rdi"
+                    } else {
+                        "xor edx, edx\n\
+                         div rdi"
+                    },
+                    wrap_around_rax(&self.ty()),
                 ),
                 Node::OperatorLt { lhs, rhs } => format!(
                     "{}\n\
@@ -189,11 +935,12 @@ pub mod x86_64 {
                      pop rdi\n\
                      pop rax\n\
                      cmp rax, rdi\n\
-                     setl al\n\
+                     {} al\n\
                      movzb rax, al\n\
                      push rax",
-                    lhs.into_x86_64_string(),
-                    rhs.into_x86_64_string(),
+                    lhs.into_x86_64_string(depth),
+                    rhs.into_x86_64_string(depth + 1),
+                    if lhs.ty().is_signed() { "setl" } else { "setb" },
                 ),
                 Node::OperatorLtEq { lhs, rhs } => format!(
                     "{}\n\
@@ -201,11 +948,16 @@ pub mod x86_64 {
                      pop rdi\n\
                      pop rax\n\
                      cmp rax, rdi\n\
-                     setle al\n\
+                     {} al\n\
                      movzb rax, al\n\
                      push rax",
-                    lhs.into_x86_64_string(),
-                    rhs.into_x86_64_string(),
+                    lhs.into_x86_64_string(depth),
+                    rhs.into_x86_64_string(depth + 1),
+                    if lhs.ty().is_signed() {
+                        "setle"
+                    } else {
+                        "setbe"
+                    },
                 ),
                 Node::OperatorEq { lhs, rhs } => format!(
                     "{}\n\
@@ -216,8 +968,8 @@ pub mod x86_64 {
                      sete al\n\
                      movzb rax, al\n\
                      push rax",
-                    lhs.into_x86_64_string(),
-                    rhs.into_x86_64_string(),
+                    lhs.into_x86_64_string(depth),
+                    rhs.into_x86_64_string(depth + 1),
                 ),
                 Node::OperatorNe { lhs, rhs } => format!(
                     "{}\n\
@@ -228,55 +980,305 @@ pub mod x86_64 {
                      setne al\n\
                      movzb rax, al\n\
                      push rax",
-                    lhs.into_x86_64_string(),
-                    rhs.into_x86_64_string(),
+                    lhs.into_x86_64_string(depth),
+                    rhs.into_x86_64_string(depth + 1),
                 ),
+                // structs are copied as a whole and the result is the address of the copy
+                Node::OperatorAssign { lhs, rhs } if lhs.ty().is_aggregate() => format!(
+                    "{}\n\
+                     {}\n\
+                     pop rsi\n\
+                     pop rdi\n\
+                     push rdi\n\
+                     {}",
+                    lhs.unref_to_rax(depth),
+                    rhs.into_x86_64_string(depth + 1),
+                    copy_memory(lhs.ty().size()),
+                ),
+                // bit-fields are stored by replacing their bits in the storage unit
+                // and the result is the value they hold afterwards
+                Node::OperatorAssign { lhs, rhs } if lhs.bit_field().is_some() => {
+                    let ty = lhs.ty();
+                    let bit_field = lhs.bit_field().unwrap();
+                    let mask = u64::MAX >> (64 - bit_field.width);
+                    format!(
+                        "{}\n\
+                         {}\n\
+                         pop rdi\n\
+                         pop rax\n\
+                         mov rsi, rax\n\
+                         {}\n\
+                         mov rdx, {:#x}\n\
+                         and rax, rdx\n\
+                         mov rdx, {:#x}\n\
+                         and rdi, rdx\n\
+                         shl rdi, {}\n\
+                         or rdi, rax\n\
+                         mov rax, rsi\n\
+                         {}\n\
+                         {}\n\
+                         push rdi",
+                        lhs.unref_to_rax(depth),
+                        rhs.into_x86_64_string(depth + 1),
+                        load_rax(&ty),
+                        !(mask << bit_field.bit_offset),
+                        mask,
+                        bit_field.bit_offset,
+                        store_rdi_to_rax(&ty),
+                        extract_bit_field(&ty, &bit_field, "rdi"),
+                    )
+                }
                 Node::OperatorAssign { lhs, rhs } => format!(
                     "{}\n\
                      {}\n\
                      pop rdi\n\
                      pop rax\n\
-                     mov [rax], rdi\n\
+                     {}\n\
                      push rdi",
-                    lhs.unref_to_rax(),
-                    rhs.into_x86_64_string(),
+                    lhs.unref_to_rax(depth),
+                    rhs.into_x86_64_string(depth + 1),
+                    store_rdi_to_rax(&lhs.ty()),
                 ),
             }
         }
 
-        fn unref_to_rax(&self) -> String {
+        fn into_x86_64_statement_string(&self) -> String {
+            if self.is_statement() {
+                self.into_x86_64_string(0)
+            } else {
+                self.into_x86_64_string(0) + "\npop rax"
+            }
+        }
+
+        fn unref_to_rax(&self, depth: usize) -> String {
             match self {
-                Node::LocalVariable {
-                    identifier: _,
+                // variables aligned more strictly than rbp are moved down to their alignments
+                Node::LocalVariable { offset, ty, .. } if ty.align() > 16 => format!(
+                    "mov rax, rbp\n\
+                     sub rax, {}\n\
+                     and rax, -{}\n\
+                     push rax",
                     offset,
-                } => format!(
+                    ty.align()
+                ),
+                Node::LocalVariable { offset, .. } => format!(
                     "mov rax, rbp\n\
                      sub rax, {}\n\
                      push rax",
                     offset
                 ),
+                Node::GlobalVariable { label, .. } => format!(
+                    "{}\n\
+                     push rax",
+                    att_syntax(&format!("leaq {}(%rip), %rax", symbol(label)))
+                ),
+                // by the initial-exec model, which the linker relaxes for variables it defines
+                Node::ThreadLocalVariable { label, .. } => format!(
+                    "mov rax, QWORD PTR fs:0\n\
+                     {}\n\
+                     push rax",
+                    att_syntax(&format!("addq {}@gottpoff(%rip), %rax", symbol(label)))
+                ),
+                Node::Dereference { value } => value.into_x86_64_string(depth),
+                Node::Member { value, member } => format!(
+                    "{}\n\
+                     pop rax\n\
+                     add rax, {}\n\
+                     push rax",
+                    value.unref_to_rax(depth),
+                    member.offset,
+                ),
+                Node::CompoundLiteral {
+                    object,
+                    initialization,
+                } => format!(
+                    "{}\n\
+                     {}",
+                    initialization_into_asm_string(initialization, depth),
+                    object.unref_to_rax(depth),
+                ),
+                Node::Function { identifier, .. } => format!(
+                    "{}\n\
+                     push rax",
+                    att_syntax(&format!("movq {}@GOTPCREL(%rip), %rax", symbol(identifier)))
+                ),
+                // structs that are not lvalues, such as results of calls, are addresses already
+                _ if self.ty().is_aggregate() => self.into_x86_64_string(depth),
                 _ => panic!("Unexpected node for left value"),
             }
         }
     }
 
-    pub fn into_asm_string(node: &Node) -> String {
-        let mut asm = node.into_x86_64_string();
-        asm.insert(0, '\n');
+    fn function_into_asm_string(function: &FunctionDefinition) -> String {
+        let mut asm = String::new();
+        // arguments are stored to the slots of their parameters first
+        // the ones on the stack are above the return address and copied after the registers are saved
+        // as the copies use some of them
+        let parameter_types = function.parameters.iter().map(Node::ty).collect::<Vec<_>>();
+        let registers = argument_registers(parameter_types.iter());
+        let mut stack_parameters = Vec::<(usize, &Type)>::new();
+        for (parameter, registers) in function.parameters.iter().zip(registers) {
+            if let Node::LocalVariable { offset, ty, .. } = parameter {
+                match registers {
+                    Some(registers) => {
+                        for (i, register) in registers.iter().enumerate() {
+                            asm += &format!(
+                                "\n{} QWORD PTR [rbp-{}], {}",
+                                if register.starts_with("xmm") {
+                                    "movq"
+                                } else {
+                                    "mov"
+                                },
+                                offset - 8 * i,
+                                register
+                            );
+                        }
+                    }
+                    None => stack_parameters.push((*offset, ty)),
+                }
+            }
+        }
+        if function.is_variadic {
+            let area = REGISTER_SAVE_AREA_SIZE;
+            for (i, register) in ARGUMENT_REGISTERS.iter().enumerate() {
+                asm += &format!("\nmov QWORD PTR [rbp-{}], {}", area - 8 * i, register);
+            }
+            for i in 0..8 {
+                asm += &format!(
+                    "\nmovq QWORD PTR [rbp-{}], xmm{}",
+                    area - 8 * ARGUMENT_REGISTERS.len() - 16 * i,
+                    i
+                );
+            }
+        }
+        let mut stack_offset = 16;
+        for (offset, ty) in stack_parameters {
+            if ty.is_aggregate() {
+                asm += &format!(
+                    "\nlea rsi, [rbp+{}]\n\
+                     lea rdi, [rbp-{}]\n\
+                     {}",
+                    stack_offset,
+                    offset,
+                    copy_memory(ty.size())
+                );
+            } else {
+                asm += &format!(
+                    "\nmov rax, QWORD PTR [rbp+{}]\n\
+                     mov QWORD PTR [rbp-{}], rax",
+                    stack_offset, offset
+                );
+            }
+            stack_offset += 8 * ty.size().div_ceil(8).max(1);
+        }
+        asm += &format!("\n{}", function.body.into_x86_64_statement_string());
         asm = asm.replace("\n", "\n    ").replace("\n    .", "\n.");
 
+        let global = if function.is_static {
+            String::new()
+        } else {
+            format!(".global {}\n", symbol(&function.name))
+        };
         format!(
-            ".intel_syntax noprefix
-.global main
-main:
+            "{0}{1}:
     push rbp
     mov rbp, rsp
-    sub rsp, 208
-{}
+    sub rsp, {2}{3}
     mov rsp, rbp
     pop rbp
     ret",
-            &asm[1..]
+            global,
+            symbol(&function.name),
+            function.stack_size,
+            asm
         )
     }
+
+    // bytes are emitted as they are except where the linker puts addresses
+    fn data_into_asm_string(data: &[u8], relocations: &[Relocation]) -> String {
+        let mut asm = String::new();
+        let mut offset = 0;
+        let emit_bytes = |asm: &mut String, bytes: &[u8]| {
+            if !bytes.is_empty() {
+                *asm += &format!(
+                    "\n.byte {}",
+                    bytes
+                        .iter()
+                        .map(u8::to_string)
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                );
+            }
+        };
+        for relocation in relocations {
+            emit_bytes(&mut asm, &data[offset..relocation.offset]);
+            asm += &format!(
+                "\n{}",
+                att_syntax(&format!(
+                    ".quad {}{:+}",
+                    symbol(&relocation.label),
+                    relocation.addend
+                ))
+            );
+            offset = relocation.offset + 8;
+        }
+        emit_bytes(&mut asm, &data[offset..]);
+
+        asm
+    }
+
+    // zero-initialized variables take no space in the object file
+    fn variable_into_asm_string(variable: &VariableDefinition) -> String {
+        let global = if variable.is_static {
+            String::new()
+        } else {
+            format!(".global {}\n", symbol(&variable.label))
+        };
+        // thread-local variables are the initial images copied for each thread
+        let (data_section, bss_section) = if variable.is_thread_local {
+            (
+                ".section .tdata,\"awT\",@progbits",
+                ".section .tbss,\"awT\",@nobits",
+            )
+        } else {
+            (".data", ".bss")
+        };
+        match &variable.data {
+            Some(data) => format!(
+                "{}\n\
+                 .align {}\n\
+                 {}{}:{}",
+                data_section,
+                variable.ty.align(),
+                global,
+                symbol(&variable.label),
+                data_into_asm_string(data, &variable.relocations),
+            ),
+            None => format!(
+                "{}\n\
+                 .align {}\n\
+                 {}{}:\n\
+                 .zero {}",
+                bss_section,
+                variable.ty.align(),
+                global,
+                symbol(&variable.label),
+                variable.ty.size()
+            ),
+        }
+    }
+
+    pub fn into_asm_string(program: &Program) -> String {
+        let mut asm = String::from(".intel_syntax noprefix\n.text");
+        for function in &program.functions {
+            asm += "\n";
+            asm += &function_into_asm_string(function);
+        }
+        for variable in &program.variables {
+            asm += "\n";
+            asm += &variable_into_asm_string(variable);
+        }
+
+        asm
+    }
 }
@@ -1,4 +1,53 @@
+use crate::compiler::types::{BitField, Member, Type};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SwitchCase {
+    // converted to the promoted type of the controlling expression
+    pub value: i64,
+    pub label: String,
+}
+
+// parameters are the local variables their arguments are stored to
+// stack_size is the size of the frame for locals, a multiple of 16 to keep rsp aligned
+#[derive(Debug, PartialEq)]
+pub struct FunctionDefinition {
+    pub name: String,
+    pub parameters: Vec<Node>,
+    pub is_variadic: bool,
+    // functions with internal linkage are not visible from other objects
+    pub is_static: bool,
+    pub stack_size: usize,
+    pub body: Node,
+}
+
+// an address to be filled in by the linker at offset in the initial image of a variable
+#[derive(Debug, PartialEq)]
+pub struct Relocation {
+    pub offset: usize,
+    pub label: String,
+    pub addend: i64,
+}
+
+// globals and static locals, which live as long as the program
+// data is the initial image, or None to zero-fill the variable in .bss
+#[derive(Debug, PartialEq)]
+pub struct VariableDefinition {
+    pub label: String,
+    pub ty: Type,
+    pub is_static: bool,
+    // each thread has its own copy of thread-local variables
+    pub is_thread_local: bool,
+    pub data: Option<Vec<u8>>,
+    pub relocations: Vec<Relocation>,
+}
+
 #[derive(Debug, PartialEq)]
+pub struct Program {
+    pub functions: Vec<FunctionDefinition>,
+    pub variables: Vec<VariableDefinition>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
 pub enum Node {
     Block {
         statements: Vec<Node>,
@@ -6,12 +55,33 @@ pub enum Node {
     Integer {
         value: i32,
     },
+    // integer constants too large for int
+    Long {
+        value: i64,
+    },
+    Floating {
+        value: f64,
+        ty: Type,
+    },
     LocalVariable {
         identifier: String,
         offset: usize,
+        ty: Type,
     },
+    GlobalVariable {
+        identifier: String,
+        label: String,
+        ty: Type,
+    },
+    // addressed relative to the thread pointer in fs
+    ThreadLocalVariable {
+        identifier: String,
+        label: String,
+        ty: Type,
+    },
+    // functions returning void may return without a value
     Return {
-        value: Box<Node>,
+        value: Option<Box<Node>>,
     },
     If {
         condition: Box<Node>,
@@ -39,9 +109,86 @@ pub enum Node {
         begin_label: String,
         end_label: String,
     },
-    FunctionCall {
+    Switch {
+        condition: Box<Node>,
+        statement: Box<Node>,
+        cases: Vec<SwitchCase>,
+        default_label: Option<String>,
+        end_label: String,
+    },
+    Label {
+        label: String,
+        statement: Box<Node>,
+    },
+    Break {
+        end_label: String,
+    },
+    Goto {
+        label: String,
+    },
+    // allocates space on the stack below the fixed locals, aligned to 16 bytes
+    // values pushed for the expression around it are moved down below the space
+    Alloca {
+        size: Box<Node>,
+    },
+    // the stack pointer is kept in the local at offset before a variable length array
+    // is allocated, so that it is released when its block exits or a goto leaves it
+    SaveStackPointer {
+        offset: usize,
+    },
+    RestoreStackPointer {
+        offset: usize,
+    },
+    // clears an object before the elements given by its initializer are stored
+    ZeroFill {
+        target: Box<Node>,
+    },
+    Function {
         identifier: String,
+        ty: Type,
+    },
+    // structs are returned to a buffer in the frame of the caller
+    FunctionCall {
+        callee: Box<Node>,
         arguments: Vec<Node>,
+        ty: Type,
+        return_buffer: Option<Box<Node>>,
+    },
+    // offsets are those of the first unnamed arguments in the register save area
+    // and among the arguments passed on the stack
+    VaStart {
+        ap: Box<Node>,
+        gp_offset: usize,
+        fp_offset: usize,
+        overflow_offset: usize,
+    },
+    VaArg {
+        ap: Box<Node>,
+        ty: Type,
+    },
+    Cast {
+        value: Box<Node>,
+        ty: Type,
+    },
+    Address {
+        value: Box<Node>,
+    },
+    Dereference {
+        value: Box<Node>,
+    },
+    Member {
+        value: Box<Node>,
+        member: Member,
+    },
+    // an unnamed local initialized every time the literal is evaluated
+    CompoundLiteral {
+        object: Box<Node>,
+        initialization: Vec<Node>,
+    },
+    // sizes of variable length arrays in a type name computed before the value that uses them
+    VariableArraySizes {
+        sizes: Vec<Node>,
+        value: Box<Node>,
     },
     OperatorAdd {
         lhs: Box<Node>,
@@ -82,13 +229,258 @@ pub enum Node {
 }
 
 impl Node {
+    pub fn is_statement(&self) -> bool {
+        matches!(
+            self,
+            Self::Block { .. }
+                | Self::Return { .. }
+                | Self::If { .. }
+                | Self::IfElse { .. }
+                | Self::While { .. }
+                | Self::For { .. }
+                | Self::Switch { .. }
+                | Self::Label { .. }
+                | Self::Break { .. }
+                | Self::Goto { .. }
+                | Self::SaveStackPointer { .. }
+                | Self::RestoreStackPointer { .. }
+                | Self::ZeroFill { .. }
+        )
+    }
+
+    pub fn evaluate_constant(&self) -> Option<i32> {
+        match self {
+            Self::Integer { value } => Some(*value),
+            Self::Cast { value, ty } => {
+                Some(ty.convert_constant(value.evaluate_constant()?.into()) as i32)
+            }
+            Self::OperatorAdd { lhs, rhs } => lhs
+                .evaluate_constant()?
+                .checked_add(rhs.evaluate_constant()?),
+            Self::OperatorSub { lhs, rhs } => lhs
+                .evaluate_constant()?
+                .checked_sub(rhs.evaluate_constant()?),
+            Self::OperatorMul { lhs, rhs } => lhs
+                .evaluate_constant()?
+                .checked_mul(rhs.evaluate_constant()?),
+            Self::OperatorDiv { lhs, rhs } => lhs
+                .evaluate_constant()?
+                .checked_div(rhs.evaluate_constant()?),
+            Self::OperatorLt { lhs, rhs } => {
+                Some((lhs.evaluate_constant()? < rhs.evaluate_constant()?) as i32)
+            }
+            Self::OperatorLtEq { lhs, rhs } => {
+                Some((lhs.evaluate_constant()? <= rhs.evaluate_constant()?) as i32)
+            }
+            Self::OperatorEq { lhs, rhs } => {
+                Some((lhs.evaluate_constant()? == rhs.evaluate_constant()?) as i32)
+            }
+            Self::OperatorNe { lhs, rhs } => {
+                Some((lhs.evaluate_constant()? != rhs.evaluate_constant()?) as i32)
+            }
+            _ => None,
+        }
+    }
+
+    // evaluates constants of any integer or pointer type, such as initializers of globals
+    // values are kept as they would be in a register, extended according to their types
+    pub fn evaluate_long_constant(&self) -> Option<i64> {
+        let value = match self {
+            Self::Integer { value } => (*value).into(),
+            Self::Long { value } => *value,
+            Self::Cast { value, ty } if value.ty().is_floating() && !ty.is_floating() => {
+                let value = value.evaluate_floating_constant()?;
+                match ty {
+                    Type::Bool => (value != 0.0).into(),
+                    Type::UnsignedLong | Type::UnsignedLongLong => value as u64 as i64,
+                    _ => value as i64,
+                }
+            }
+            Self::Cast { value, ty } if !ty.is_floating() => value.evaluate_long_constant()?,
+            Self::OperatorAdd { lhs, rhs } => lhs
+                .evaluate_long_constant()?
+                .wrapping_add(rhs.evaluate_long_constant()?),
+            Self::OperatorSub { lhs, rhs } => lhs
+                .evaluate_long_constant()?
+                .wrapping_sub(rhs.evaluate_long_constant()?),
+            Self::OperatorMul { lhs, rhs } => lhs
+                .evaluate_long_constant()?
+                .wrapping_mul(rhs.evaluate_long_constant()?),
+            Self::OperatorDiv { lhs, rhs } => {
+                let (lhs, rhs) = (lhs.evaluate_long_constant()?, rhs.evaluate_long_constant()?);
+                if self.ty().is_signed() {
+                    lhs.checked_div(rhs)?
+                } else {
+                    (lhs as u64).checked_div(rhs as u64)? as i64
+                }
+            }
+            Self::OperatorLt { lhs, rhs } | Self::OperatorLtEq { lhs, rhs } => {
+                let is_signed = lhs.ty().is_signed();
+                let (lhs, rhs) = (lhs.evaluate_long_constant()?, rhs.evaluate_long_constant()?);
+                let ordering = if is_signed {
+                    lhs.cmp(&rhs)
+                } else {
+                    (lhs as u64).cmp(&(rhs as u64))
+                };
+                match self {
+                    Self::OperatorLt { .. } => ordering.is_lt().into(),
+                    _ => ordering.is_le().into(),
+                }
+            }
+            Self::OperatorEq { lhs, rhs } => {
+                (lhs.evaluate_long_constant()? == rhs.evaluate_long_constant()?).into()
+            }
+            Self::OperatorNe { lhs, rhs } => {
+                (lhs.evaluate_long_constant()? != rhs.evaluate_long_constant()?).into()
+            }
+            _ => return None,
+        };
+
+        Some(self.ty().convert_constant(value))
+    }
+
+    // evaluates pointers to objects with static storage duration as a label and an offset
+    pub fn evaluate_address_constant(&self) -> Option<(String, i64)> {
+        match self {
+            Self::Address { value } => value.evaluate_location(),
+            Self::Cast { value, ty } if ty.size() == 8 && !ty.is_floating() => {
+                value.evaluate_address_constant()
+            }
+            Self::OperatorAdd { lhs, rhs } => {
+                let (label, addend) = lhs.evaluate_address_constant()?;
+                Some((label, addend + rhs.evaluate_long_constant()?))
+            }
+            Self::OperatorSub { lhs, rhs } if !rhs.ty().is_pointer() => {
+                let (label, addend) = lhs.evaluate_address_constant()?;
+                Some((label, addend - rhs.evaluate_long_constant()?))
+            }
+            _ => None,
+        }
+    }
+
+    fn evaluate_location(&self) -> Option<(String, i64)> {
+        match self {
+            Self::GlobalVariable { label, .. } => Some((label.to_owned(), 0)),
+            Self::Function { identifier, .. } => Some((identifier.to_owned(), 0)),
+            Self::Member { value, member } => {
+                let (label, addend) = value.evaluate_location()?;
+                Some((label, addend + member.offset as i64))
+            }
+            Self::Dereference { value } => value.evaluate_address_constant(),
+            _ => None,
+        }
+    }
+
+    pub fn evaluate_floating_constant(&self) -> Option<f64> {
+        match self {
+            Self::Floating { value, .. } => Some(*value),
+            Self::Cast { value, ty } if ty.is_floating() => value.evaluate_floating_constant(),
+            _ => self.evaluate_long_constant().map(|value| {
+                if self.ty().is_signed() || self.ty().size() < 8 {
+                    value as f64
+                } else {
+                    value as u64 as f64
+                }
+            }),
+        }
+    }
+
+    // the type of the object designated by an lvalue, with its qualifiers
+    fn object_ty(&self) -> Option<Type> {
+        match self {
+            Self::LocalVariable { ty, .. }
+            | Self::GlobalVariable { ty, .. }
+            | Self::ThreadLocalVariable { ty, .. } => Some(ty.to_owned()),
+            Self::Dereference { value } => value.ty().pointee().cloned(),
+            // members of const structs are const too
+            Self::Member { value, member } => Some(Type::qualified(
+                member.ty.to_owned(),
+                value.object_ty().is_some_and(|ty| ty.is_const()),
+                false,
+            )),
+            Self::CompoundLiteral { object, .. } => object.object_ty(),
+            _ => None,
+        }
+    }
+
+    // structs with const members cannot be assigned to as a whole either
+    pub fn is_read_only(&self) -> bool {
+        self.object_ty()
+            .is_some_and(|ty| ty.is_const() || ty.has_const_member())
+    }
+
     pub fn is_left_value(&self) -> bool {
         match self {
-            Self::LocalVariable {
-                identifier: _,
-                offset: _,
-            } => true,
+            Self::LocalVariable { .. }
+            | Self::GlobalVariable { .. }
+            | Self::ThreadLocalVariable { .. }
+            | Self::Dereference { .. } => true,
+            Self::Member { value, .. } => value.is_left_value(),
+            Self::CompoundLiteral { .. } => true,
             _ => false,
         }
     }
+
+    pub fn bit_field(&self) -> Option<BitField> {
+        match self {
+            Self::Member { member, .. } => member.bit_field,
+            _ => None,
+        }
+    }
+
+    pub fn ty(&self) -> Type {
+        match self {
+            Self::Integer { .. } => Type::Int,
+            Self::Long { .. } => Type::Long,
+            Self::Floating { ty, .. } => ty.to_owned(),
+            Self::LocalVariable { ty, .. } => ty.unqualified().to_owned(),
+            Self::GlobalVariable { ty, .. } | Self::ThreadLocalVariable { ty, .. } => {
+                ty.unqualified().to_owned()
+            }
+            Self::Function { ty, .. } => ty.to_owned(),
+            Self::FunctionCall { ty, .. } => ty.to_owned(),
+            Self::VaStart { .. } => Type::Void,
+            Self::VaArg { ty, .. } => ty.to_owned(),
+            Self::Cast { ty, .. } => ty.to_owned(),
+            Self::Address { value } => Type::pointer_to(value.object_ty().unwrap_or(value.ty())),
+            Self::Dereference { value } => value
+                .ty()
+                .pointee()
+                .expect("dereference of non-pointer")
+                .unqualified()
+                .to_owned(),
+            Self::Member { member, .. } => member.ty.unqualified().to_owned(),
+            Self::CompoundLiteral { object, .. } => object.ty(),
+            Self::VariableArraySizes { value, .. } => value.ty(),
+            Self::Alloca { .. } => Type::pointer_to(Type::Void),
+            Self::OperatorAdd { lhs, rhs } | Self::OperatorSub { lhs, rhs } => {
+                match (lhs.ty(), rhs.ty()) {
+                    (Type::Pointer(_), Type::Pointer(_)) => Type::Long,
+                    (lhs @ Type::Pointer(_), _) => lhs,
+                    (_, rhs @ Type::Pointer(_)) => rhs,
+                    // operands have been converted to their common type
+                    (lhs, _) => lhs,
+                }
+            }
+            Self::OperatorMul { lhs, .. } | Self::OperatorDiv { lhs, .. } => lhs.ty(),
+            Self::OperatorLt { .. }
+            | Self::OperatorLtEq { .. }
+            | Self::OperatorEq { .. }
+            | Self::OperatorNe { .. } => Type::Int,
+            Self::OperatorAssign { lhs, .. } => lhs.ty(),
+            Self::Block { .. }
+            | Self::Return { .. }
+            | Self::If { .. }
+            | Self::IfElse { .. }
+            | Self::While { .. }
+            | Self::For { .. }
+            | Self::Switch { .. }
+            | Self::Label { .. }
+            | Self::Break { .. }
+            | Self::Goto { .. }
+            | Self::SaveStackPointer { .. }
+            | Self::RestoreStackPointer { .. }
+            | Self::ZeroFill { .. } => unreachable!("statements do not have a type"),
+        }
+    }
 }
@@ -4,8 +4,12 @@ use crate::compiler::parser::Parser;
 
 pub fn compile(text: &str) -> Result<String> {
     let text = text.trim();
-    let node = Parser::new(text).parse()?;
-    let result = x86_64::into_asm_string(&node);
+    let mut parser = Parser::new(text);
+    let program = parser.parse()?;
+    for warning in parser.take_warnings() {
+        eprint!("{}", warning.into_formatter(text));
+    }
+    let result = x86_64::into_asm_string(&program);
 
     Ok(result)
 }
@@ -8,17 +8,46 @@ pub type Result<T = ()> = std::result::Result<T, CompileError>;
 impl TokenKind {
     fn token_kind_display(&self) -> &str {
         match self {
-            Self::Integer(_) => "integer",
+            Self::Integer(_) | Self::IntegerTooLarge => "integer",
+            Self::Float(_) | Self::Double(_) => "floating constant",
             Self::Identifier(_) => "identifier",
+            Self::String(_) => "string literal",
             Self::KeywordReturn => "return",
             Self::KeywordIf => "if",
             Self::KeywordElse => "else",
             Self::KeywordWhile => "while",
             Self::KeywordFor => "for",
+            Self::KeywordSwitch => "switch",
+            Self::KeywordCase => "case",
+            Self::KeywordDefault => "default",
+            Self::KeywordBreak => "break",
+            Self::KeywordGoto => "goto",
+            Self::KeywordChar => "char",
+            Self::KeywordShort => "short",
+            Self::KeywordInt => "int",
+            Self::KeywordLong => "long",
+            Self::KeywordSigned => "signed",
+            Self::KeywordUnsigned => "unsigned",
+            Self::KeywordFloat => "float",
+            Self::KeywordDouble => "double",
+            Self::KeywordVoid => "void",
+            Self::KeywordVaList => "va_list",
+            Self::KeywordBool => "_Bool",
+            Self::KeywordConst => "const",
+            Self::KeywordVolatile => "volatile",
+            Self::KeywordStatic => "static",
+            Self::KeywordExtern => "extern",
+            Self::KeywordThreadLocal => "_Thread_local",
+            Self::KeywordStruct => "struct",
+            Self::KeywordAlignas => "_Alignas",
+            Self::KeywordAlignof => "_Alignof",
+            Self::KeywordSizeof => "sizeof",
+            Self::KeywordGeneric => "_Generic",
             Self::SymbolPlus => "'+'",
             Self::SymbolMinus => "'-'",
             Self::SymbolStar => "'*'",
             Self::SymbolSlash => "'/'",
+            Self::SymbolAmpersand => "'&'",
             TokenKind::SymbolRoundBracketLeft => "'('",
             Self::SymbolRoundBracketRight => "')'",
             Self::SymbolAngleBracketLeft => "'<'",
@@ -27,21 +56,67 @@ impl TokenKind {
             Self::SymbolAngleBracketRightAndEqual => "'>='",
             Self::SymbolCurlyBracketLeft => "'{'",
             Self::SymbolCurlyBracketRight => "'}'",
+            Self::SymbolSquareBracketLeft => "'['",
+            Self::SymbolSquareBracketRight => "']'",
+            Self::SymbolDot => "'.'",
+            Self::SymbolArrow => "'->'",
             Self::SymbolDoubleEqual => "'=='",
             Self::SymbolExclamationAndEqual => "'!='",
             Self::SymbolEqual => "'='",
+            Self::SymbolColon => "':'",
             Self::SymbolSemicolon => "';'",
             Self::SymbolComma => "','",
-            Self::EOF => "EOF",
+            Self::SymbolEllipsis => "'...'",
+            Self::Eof => "EOF",
         }
     }
 }
 
+#[derive(Debug)]
+pub enum NotALeftValueKind {
+    Required,
+    ReadOnly,
+}
+
 #[derive(Debug)]
 pub enum CompileErrorKind {
     UnexpectedToken { expected: Vec<TokenKind> },
     UnexpectedEOF,
-    NotALeftValue,
+    NotALeftValue { kind: NotALeftValueKind },
+    NotAConstant,
+    IntegerTooLarge,
+    DuplicateCaseValue { value: i64 },
+    DuplicateDefault,
+    CaseOutsideOfSwitch,
+    BreakOutsideOfLoop,
+    UndefinedLabel { name: String },
+    DuplicateLabel { name: String },
+    JumpIntoVariableArrayScope { name: String },
+    SwitchIntoVariableArrayScope,
+    Redefinition { name: String },
+    ConflictingTypes { name: String },
+    InvalidTypeSpecifier,
+    NotAPointer,
+    NotAFunction,
+    NotVariadic,
+    NotAVaList,
+    NotAStruct,
+    NotAScalar,
+    NotAnInteger,
+    InvalidOperands,
+    VoidValue,
+    UnknownMember { name: String },
+    IncompleteType { name: String },
+    ExcessInitializer,
+    ArgumentCount { expected: usize, actual: usize },
+    IncompatibleArgument { position: usize },
+    IncompatibleAssignment,
+    InvalidCast,
+    InvalidBitField,
+    InvalidAlignment,
+    InvalidStorageClass,
+    DuplicateAssociation,
+    NoMatchingAssociation,
 }
 
 #[derive(Debug)]
@@ -67,7 +142,256 @@ impl CompileError {
 
     pub fn not_a_left_value(index_start: usize) -> Self {
         Self {
-            kind: CompileErrorKind::NotALeftValue,
+            kind: CompileErrorKind::NotALeftValue {
+                kind: NotALeftValueKind::Required,
+            },
+            index_start,
+        }
+    }
+
+    pub fn read_only_left_value(index_start: usize) -> Self {
+        Self {
+            kind: CompileErrorKind::NotALeftValue {
+                kind: NotALeftValueKind::ReadOnly,
+            },
+            index_start,
+        }
+    }
+
+    pub fn integer_too_large(index_start: usize) -> Self {
+        Self {
+            kind: CompileErrorKind::IntegerTooLarge,
+            index_start,
+        }
+    }
+
+    pub fn not_a_constant(index_start: usize) -> Self {
+        Self {
+            kind: CompileErrorKind::NotAConstant,
+            index_start,
+        }
+    }
+
+    pub fn duplicate_case_value(index_start: usize, value: i64) -> Self {
+        Self {
+            kind: CompileErrorKind::DuplicateCaseValue { value },
+            index_start,
+        }
+    }
+
+    pub fn duplicate_default(index_start: usize) -> Self {
+        Self {
+            kind: CompileErrorKind::DuplicateDefault,
+            index_start,
+        }
+    }
+
+    pub fn case_outside_of_switch(index_start: usize) -> Self {
+        Self {
+            kind: CompileErrorKind::CaseOutsideOfSwitch,
+            index_start,
+        }
+    }
+
+    pub fn break_outside_of_loop(index_start: usize) -> Self {
+        Self {
+            kind: CompileErrorKind::BreakOutsideOfLoop,
+            index_start,
+        }
+    }
+
+    pub fn undefined_label(index_start: usize, name: String) -> Self {
+        Self {
+            kind: CompileErrorKind::UndefinedLabel { name },
+            index_start,
+        }
+    }
+
+    pub fn duplicate_label(index_start: usize, name: String) -> Self {
+        Self {
+            kind: CompileErrorKind::DuplicateLabel { name },
+            index_start,
+        }
+    }
+
+    pub fn jump_into_variable_array_scope(index_start: usize, name: String) -> Self {
+        Self {
+            kind: CompileErrorKind::JumpIntoVariableArrayScope { name },
+            index_start,
+        }
+    }
+
+    pub fn switch_into_variable_array_scope(index_start: usize) -> Self {
+        Self {
+            kind: CompileErrorKind::SwitchIntoVariableArrayScope,
+            index_start,
+        }
+    }
+
+    pub fn redefinition(index_start: usize, name: String) -> Self {
+        Self {
+            kind: CompileErrorKind::Redefinition { name },
+            index_start,
+        }
+    }
+
+    pub fn conflicting_types(index_start: usize, name: String) -> Self {
+        Self {
+            kind: CompileErrorKind::ConflictingTypes { name },
+            index_start,
+        }
+    }
+
+    pub fn invalid_type_specifier(index_start: usize) -> Self {
+        Self {
+            kind: CompileErrorKind::InvalidTypeSpecifier,
+            index_start,
+        }
+    }
+
+    pub fn not_a_pointer(index_start: usize) -> Self {
+        Self {
+            kind: CompileErrorKind::NotAPointer,
+            index_start,
+        }
+    }
+
+    pub fn not_a_function(index_start: usize) -> Self {
+        Self {
+            kind: CompileErrorKind::NotAFunction,
+            index_start,
+        }
+    }
+
+    pub fn not_variadic(index_start: usize) -> Self {
+        Self {
+            kind: CompileErrorKind::NotVariadic,
+            index_start,
+        }
+    }
+
+    pub fn not_a_va_list(index_start: usize) -> Self {
+        Self {
+            kind: CompileErrorKind::NotAVaList,
+            index_start,
+        }
+    }
+
+    pub fn not_a_struct(index_start: usize) -> Self {
+        Self {
+            kind: CompileErrorKind::NotAStruct,
+            index_start,
+        }
+    }
+
+    pub fn not_a_scalar(index_start: usize) -> Self {
+        Self {
+            kind: CompileErrorKind::NotAScalar,
+            index_start,
+        }
+    }
+
+    pub fn not_an_integer(index_start: usize) -> Self {
+        Self {
+            kind: CompileErrorKind::NotAnInteger,
+            index_start,
+        }
+    }
+
+    pub fn invalid_operands(index_start: usize) -> Self {
+        Self {
+            kind: CompileErrorKind::InvalidOperands,
+            index_start,
+        }
+    }
+
+    pub fn void_value(index_start: usize) -> Self {
+        Self {
+            kind: CompileErrorKind::VoidValue,
+            index_start,
+        }
+    }
+
+    pub fn unknown_member(index_start: usize, name: String) -> Self {
+        Self {
+            kind: CompileErrorKind::UnknownMember { name },
+            index_start,
+        }
+    }
+
+    pub fn incomplete_type(index_start: usize, name: String) -> Self {
+        Self {
+            kind: CompileErrorKind::IncompleteType { name },
+            index_start,
+        }
+    }
+
+    pub fn excess_initializer(index_start: usize) -> Self {
+        Self {
+            kind: CompileErrorKind::ExcessInitializer,
+            index_start,
+        }
+    }
+
+    pub fn argument_count(index_start: usize, expected: usize, actual: usize) -> Self {
+        Self {
+            kind: CompileErrorKind::ArgumentCount { expected, actual },
+            index_start,
+        }
+    }
+
+    pub fn incompatible_argument(index_start: usize, position: usize) -> Self {
+        Self {
+            kind: CompileErrorKind::IncompatibleArgument { position },
+            index_start,
+        }
+    }
+
+    pub fn incompatible_assignment(index_start: usize) -> Self {
+        Self {
+            kind: CompileErrorKind::IncompatibleAssignment,
+            index_start,
+        }
+    }
+
+    pub fn invalid_cast(index_start: usize) -> Self {
+        Self {
+            kind: CompileErrorKind::InvalidCast,
+            index_start,
+        }
+    }
+
+    pub fn invalid_bit_field(index_start: usize) -> Self {
+        Self {
+            kind: CompileErrorKind::InvalidBitField,
+            index_start,
+        }
+    }
+
+    pub fn invalid_alignment(index_start: usize) -> Self {
+        Self {
+            kind: CompileErrorKind::InvalidAlignment,
+            index_start,
+        }
+    }
+
+    pub fn invalid_storage_class(index_start: usize) -> Self {
+        Self {
+            kind: CompileErrorKind::InvalidStorageClass,
+            index_start,
+        }
+    }
+
+    pub fn duplicate_association(index_start: usize) -> Self {
+        Self {
+            kind: CompileErrorKind::DuplicateAssociation,
+            index_start,
+        }
+    }
+
+    pub fn no_matching_association(index_start: usize) -> Self {
+        Self {
+            kind: CompileErrorKind::NoMatchingAssociation,
             index_start,
         }
     }
@@ -97,9 +421,185 @@ impl fmt::Display for CompileError {
             CompileErrorKind::UnexpectedEOF => {
                 writeln!(f, "unexpected EOF at {}", self.index_start)?;
             }
-            CompileErrorKind::NotALeftValue => {
+            CompileErrorKind::NotALeftValue {
+                kind: NotALeftValueKind::Required,
+            } => {
                 writeln!(f, "left value expected at {}", self.index_start)?;
             }
+            CompileErrorKind::NotALeftValue {
+                kind: NotALeftValueKind::ReadOnly,
+            } => {
+                writeln!(
+                    f,
+                    "assignment of read-only left value at {}",
+                    self.index_start
+                )?;
+            }
+            CompileErrorKind::IntegerTooLarge => {
+                writeln!(f, "integer constant is too large at {}", self.index_start)?;
+            }
+            CompileErrorKind::NotAConstant => {
+                writeln!(f, "integer constant expected at {}", self.index_start)?;
+            }
+            CompileErrorKind::DuplicateCaseValue { value } => {
+                writeln!(f, "duplicate case value {} at {}", value, self.index_start)?;
+            }
+            CompileErrorKind::DuplicateDefault => {
+                writeln!(f, "multiple default labels at {}", self.index_start)?;
+            }
+            CompileErrorKind::CaseOutsideOfSwitch => {
+                writeln!(f, "case label not within a switch at {}", self.index_start)?;
+            }
+            CompileErrorKind::BreakOutsideOfLoop => {
+                writeln!(
+                    f,
+                    "break statement not within loop or switch at {}",
+                    self.index_start
+                )?;
+            }
+            CompileErrorKind::UndefinedLabel { ref name } => {
+                writeln!(
+                    f,
+                    "label '{}' used but not defined at {}",
+                    name, self.index_start
+                )?;
+            }
+            CompileErrorKind::DuplicateLabel { ref name } => {
+                writeln!(f, "duplicate label '{}' at {}", name, self.index_start)?;
+            }
+            CompileErrorKind::SwitchIntoVariableArrayScope => {
+                writeln!(
+                    f,
+                    "switch jumps into the scope of a variable length array at {}",
+                    self.index_start
+                )?;
+            }
+            CompileErrorKind::JumpIntoVariableArrayScope { ref name } => {
+                writeln!(
+                    f,
+                    "jump to label '{}' into the scope of a variable length array at {}",
+                    name, self.index_start
+                )?;
+            }
+            CompileErrorKind::Redefinition { ref name } => {
+                writeln!(f, "redefinition of '{}' at {}", name, self.index_start)?;
+            }
+            CompileErrorKind::ConflictingTypes { ref name } => {
+                writeln!(
+                    f,
+                    "conflicting types for '{}' at {}",
+                    name, self.index_start
+                )?;
+            }
+            CompileErrorKind::InvalidTypeSpecifier => {
+                writeln!(
+                    f,
+                    "invalid combination of type specifiers at {}",
+                    self.index_start
+                )?;
+            }
+            CompileErrorKind::NotAPointer => {
+                writeln!(f, "pointer expected at {}", self.index_start)?;
+            }
+            CompileErrorKind::NotAFunction => {
+                writeln!(f, "called object is not a function at {}", self.index_start)?;
+            }
+            CompileErrorKind::NotVariadic => {
+                writeln!(
+                    f,
+                    "va_start used in function with fixed arguments at {}",
+                    self.index_start
+                )?;
+            }
+            CompileErrorKind::NotAVaList => {
+                writeln!(f, "va_list expected at {}", self.index_start)?;
+            }
+            CompileErrorKind::NotAStruct => {
+                writeln!(f, "struct expected at {}", self.index_start)?;
+            }
+            CompileErrorKind::NotAScalar => {
+                writeln!(f, "scalar type expected at {}", self.index_start)?;
+            }
+            CompileErrorKind::NotAnInteger => {
+                writeln!(f, "integer type expected at {}", self.index_start)?;
+            }
+            CompileErrorKind::InvalidOperands => {
+                writeln!(f, "invalid operands at {}", self.index_start)?;
+            }
+            CompileErrorKind::VoidValue => {
+                writeln!(
+                    f,
+                    "void value not ignored as it ought to be at {}",
+                    self.index_start
+                )?;
+            }
+            CompileErrorKind::UnknownMember { ref name } => {
+                writeln!(f, "no member named '{}' at {}", name, self.index_start)?;
+            }
+            CompileErrorKind::IncompleteType { ref name } => {
+                writeln!(
+                    f,
+                    "incomplete type '{}' used where a complete one is required at {}",
+                    name, self.index_start
+                )?;
+            }
+            CompileErrorKind::ExcessInitializer => {
+                writeln!(f, "excess elements in initializer at {}", self.index_start)?;
+            }
+            CompileErrorKind::ArgumentCount { expected, actual } => {
+                writeln!(
+                    f,
+                    "too {} arguments to function, {} expected but {} given at {}",
+                    if actual < expected { "few" } else { "many" },
+                    expected,
+                    actual,
+                    self.index_start
+                )?;
+            }
+            CompileErrorKind::IncompatibleArgument { position } => {
+                writeln!(
+                    f,
+                    "incompatible type for argument {} at {}",
+                    position, self.index_start
+                )?;
+            }
+            CompileErrorKind::IncompatibleAssignment => {
+                writeln!(
+                    f,
+                    "incompatible types in assignment at {}",
+                    self.index_start
+                )?;
+            }
+            CompileErrorKind::InvalidCast => {
+                writeln!(
+                    f,
+                    "cannot convert between pointer and floating types at {}",
+                    self.index_start
+                )?;
+            }
+            CompileErrorKind::InvalidBitField => {
+                writeln!(f, "invalid bit-field at {}", self.index_start)?;
+            }
+            CompileErrorKind::InvalidAlignment => {
+                writeln!(f, "invalid alignment at {}", self.index_start)?;
+            }
+            CompileErrorKind::InvalidStorageClass => {
+                writeln!(f, "invalid storage class at {}", self.index_start)?;
+            }
+            CompileErrorKind::DuplicateAssociation => {
+                writeln!(
+                    f,
+                    "duplicate type in generic association at {}",
+                    self.index_start
+                )?;
+            }
+            CompileErrorKind::NoMatchingAssociation => {
+                writeln!(
+                    f,
+                    "no generic association matches the controlling type at {}",
+                    self.index_start
+                )?;
+            }
         }
 
         Ok(())
@@ -133,3 +633,68 @@ impl<'a> fmt::Display for CompileErrorFormatter<'a> {
         Ok(())
     }
 }
+
+#[derive(Debug, PartialEq)]
+pub enum CompileWarningKind {
+    ImplicitFunctionDeclaration { name: String },
+}
+
+// warnings do not stop the compilation and are reported along with its result
+#[derive(Debug, PartialEq)]
+pub struct CompileWarning {
+    kind: CompileWarningKind,
+    index_start: usize,
+}
+
+impl CompileWarning {
+    pub fn implicit_function_declaration(index_start: usize, name: String) -> Self {
+        Self {
+            kind: CompileWarningKind::ImplicitFunctionDeclaration { name },
+            index_start,
+        }
+    }
+
+    pub fn into_formatter<'a>(self, text: &'a str) -> CompileWarningFormatter<'a> {
+        CompileWarningFormatter::new(self, text)
+    }
+}
+
+impl fmt::Display for CompileWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Compile warning: ")?;
+
+        match self.kind {
+            CompileWarningKind::ImplicitFunctionDeclaration { ref name } => {
+                writeln!(
+                    f,
+                    "implicit declaration of function '{}' at {}",
+                    name, self.index_start
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+pub struct CompileWarningFormatter<'a> {
+    warning: CompileWarning,
+    text: &'a str,
+}
+
+impl<'a> CompileWarningFormatter<'a> {
+    pub fn new(warning: CompileWarning, text: &'a str) -> Self {
+        Self { warning, text }
+    }
+}
+
+impl<'a> fmt::Display for CompileWarningFormatter<'a> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "{}", self.warning)?;
+
+        writeln!(f, "{}", self.text.trim_end())?;
+        writeln!(f, "{}^", " ".repeat(self.warning.index_start))?;
+
+        Ok(())
+    }
+}
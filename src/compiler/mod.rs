@@ -1,3 +1,4 @@
+pub mod abi;
 pub mod asm;
 pub mod ast;
 mod compile;
@@ -5,5 +6,6 @@ pub mod error;
 pub mod parser;
 pub mod token;
 pub mod tokenizer;
+pub mod types;
 
 pub use compile::*;
@@ -1,39 +1,273 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::iter::Peekable;
 
-use crate::compiler::ast::Node;
-use crate::compiler::error::{CompileError, Result};
+use crate::compiler::abi::{argument_registers, eightbyte_classes, REGISTER_SAVE_AREA_SIZE};
+use crate::compiler::ast::{
+    FunctionDefinition, Node, Program, Relocation, SwitchCase, VariableDefinition,
+};
+use crate::compiler::error::{CompileError, CompileWarning, Result};
 use crate::compiler::token::TokenKind;
 use crate::compiler::tokenizer::{Tokenizer, TokenizerIterator};
+use crate::compiler::types::{BitField, Type};
+
+#[derive(Clone)]
+enum Variable {
+    Local {
+        offset: usize,
+        ty: Type,
+    },
+    // variable length arrays are accessed through the pointers to them kept at offset
+    Array {
+        offset: usize,
+        ty: Type,
+    },
+    // globals and static locals are accessed through their labels
+    Global {
+        label: String,
+        ty: Type,
+        is_thread_local: bool,
+    },
+}
+
+impl Variable {
+    fn into_node(self, identifier: String) -> Node {
+        match self {
+            Self::Local { offset, ty } => Node::LocalVariable {
+                identifier,
+                offset,
+                ty,
+            },
+            Self::Array { offset, ty } => Node::Dereference {
+                value: Node::LocalVariable {
+                    identifier,
+                    offset,
+                    ty: Type::pointer_to(ty),
+                }
+                .into(),
+            },
+            Self::Global {
+                label,
+                ty,
+                is_thread_local: true,
+            } => Node::ThreadLocalVariable {
+                identifier,
+                label,
+                ty,
+            },
+            Self::Global { label, ty, .. } => Node::GlobalVariable {
+                identifier,
+                label,
+                ty,
+            },
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum StorageClass {
+    Static,
+    Extern,
+}
 
 struct VariableScope {
-    variable_offsets: HashMap<String, usize>,
-    // should be equals to variables.len()
-    // cached due to HashMap.len() seems to be O(n) operation
-    variable_count: usize,
+    variables: HashMap<String, Variable>,
+    // struct types by their tags
+    tags: HashMap<String, Type>,
+    // offset of the deepest slot used by this scope and its parents
+    // a new scope starts from its parent's so that sibling scopes share slots
+    last_offset: usize,
+    // variable length arrays of this scope as pairs of unique ids and offsets of the locals
+    // keeping the stack pointer from before each of them was allocated
+    variable_arrays: Vec<(usize, usize)>,
 }
 
 impl VariableScope {
-    pub fn new() -> Self {
+    pub fn new(last_offset: usize) -> Self {
         Self {
-            variable_offsets: HashMap::new(),
-            variable_count: 0,
+            variables: HashMap::new(),
+            tags: HashMap::new(),
+            last_offset,
+            variable_arrays: Vec::new(),
         }
     }
 
-    pub fn get_or_insert_offset_by_name(&mut self, identifier: String) -> usize {
-        *self.variable_offsets.entry(identifier).or_insert_with(|| {
-            self.variable_count += 1;
-            self.variable_count * 8
-        })
+    pub fn get_variable_by_name(&self, identifier: &str) -> Option<&Variable> {
+        self.variables.get(identifier)
+    }
+
+    pub fn insert_variable(&mut self, identifier: String, ty: Type) -> Option<Variable> {
+        if self.variables.contains_key(&identifier) {
+            return None;
+        }
+
+        // every variable takes slots of 8 bytes, at most 16 of which rbp is aligned to
+        // so variables aligned more strictly get room to be moved down to their alignments
+        let offset = (self.last_offset + ty.size().div_ceil(8) * 8)
+            .next_multiple_of(ty.align().clamp(8, 16));
+        self.last_offset = offset + ty.align().max(16) - 16;
+        let variable = Variable::Local { offset, ty };
+        self.variables.insert(identifier, variable.to_owned());
+        Some(variable)
+    }
+
+    pub fn insert_variable_array(&mut self, identifier: String, ty: Type) -> Option<Variable> {
+        let Variable::Local { offset, .. } =
+            self.insert_variable(identifier.to_owned(), Type::pointer_to(ty.to_owned()))?
+        else {
+            unreachable!("pointers are locals")
+        };
+        let variable = Variable::Array { offset, ty };
+        self.variables.insert(identifier, variable.to_owned());
+        Some(variable)
+    }
+
+    pub fn insert_global(
+        &mut self,
+        identifier: String,
+        label: String,
+        ty: Type,
+        is_thread_local: bool,
+    ) -> Option<Variable> {
+        if self.variables.contains_key(&identifier) {
+            return None;
+        }
+
+        let variable = Variable::Global {
+            label,
+            ty,
+            is_thread_local,
+        };
+        self.variables.insert(identifier, variable.to_owned());
+        Some(variable)
+    }
+
+    // globals of arrays of unknown length are declared before the initializers completing them
+    pub fn complete_global(&mut self, identifier: &str, ty: Type) {
+        if let Some(Variable::Global {
+            ty: declared_ty, ..
+        }) = self.variables.get_mut(identifier)
+        {
+            *declared_ty = ty;
+        }
+    }
+}
+
+struct SwitchContext {
+    label_suffix: usize,
+    // promoted type of the controlling expression, to which case values are converted
+    ty: Type,
+    // variable length arrays in scope of the switch, which its labels cannot add to
+    variable_arrays: Vec<(usize, usize)>,
+    cases: Vec<SwitchCase>,
+    default_label: Option<String>,
+}
+
+impl SwitchContext {
+    pub fn new(label_suffix: usize, ty: Type, variable_arrays: Vec<(usize, usize)>) -> Self {
+        Self {
+            label_suffix,
+            ty,
+            variable_arrays,
+            cases: Vec::new(),
+            default_label: None,
+        }
+    }
+}
+
+// state of the function being parsed
+// statements outside of function definitions form the body of main
+struct FunctionContext {
+    name: String,
+    return_ty: Type,
+    parameter_types: Vec<Type>,
+    is_variadic: bool,
+    // hidden parameter pointing to where structs returned in memory are stored
+    return_pointer: Option<Node>,
+    variable_scopes: Vec<VariableScope>,
+    // offset of the deepest slot used by any scope
+    stack_size: usize,
+    // stores of the sizes of variable length arrays in the declarator being parsed
+    // which are run before the declared variable is
+    variable_array_sizes: Vec<Node>,
+    // variable length arrays in scope at each label
+    defined_labels: HashMap<String, Vec<(usize, usize)>>,
+    goto_targets: Vec<GotoTarget>,
+}
+
+struct GotoTarget {
+    name: String,
+    // index of the label name in text
+    index: usize,
+    // label jumped to, which is one of the goto's own if it may leave variable length arrays
+    label: String,
+    variable_arrays: Vec<(usize, usize)>,
+}
+
+impl FunctionContext {
+    pub fn new(name: String, ty: Type) -> Self {
+        let Type::Function {
+            return_ty,
+            parameters,
+            is_variadic,
+            ..
+        } = ty
+        else {
+            unreachable!("functions have function types")
+        };
+        // the register save area of variadic functions comes before their locals
+        let stack_size = if is_variadic {
+            REGISTER_SAVE_AREA_SIZE
+        } else {
+            0
+        };
+        Self {
+            name,
+            return_ty: *return_ty,
+            parameter_types: parameters,
+            is_variadic,
+            return_pointer: None,
+            variable_scopes: vec![VariableScope::new(stack_size)],
+            stack_size,
+            variable_array_sizes: Vec::new(),
+            defined_labels: HashMap::new(),
+            goto_targets: Vec::new(),
+        }
     }
 }
 
+// elements left out of an initializer list are zero
+enum Initializer {
+    Expression(Node),
+    List(Vec<Option<Initializer>>),
+}
+
+// identifiers are omitted in abstract declarators
+// parameters are those of the declared function, if it is one
+struct Declarator {
+    identifier: Option<String>,
+    index: usize,
+    ty: Type,
+    parameters: Vec<Declarator>,
+}
+
 pub struct Parser<'a> {
     text: &'a str,
     tokens: Peekable<TokenizerIterator<'a>>,
-    variable_scopes: Vec<VariableScope>,
+    function: FunctionContext,
     last_label_suffix: usize,
+    // end labels of the statements break exits, along with the numbers of scopes outside of them
+    break_labels: Vec<(String, usize)>,
+    switch_contexts: Vec<SwitchContext>,
+    // types of declared functions
+    functions: HashMap<String, Type>,
+    defined_functions: HashSet<String>,
+    static_functions: HashSet<String>,
+    // variables declared outside of functions, which are visible in all of them
+    file_scope: VariableScope,
+    variables: Vec<VariableDefinition>,
+    // index of the first initializer of a global that has to run in the implicit main
+    first_dynamic_initializer_index: Option<usize>,
+    warnings: Vec<CompileWarning>,
 }
 
 impl<'a> Parser<'a> {
@@ -42,19 +276,68 @@ impl<'a> Parser<'a> {
         Self {
             text,
             tokens: tokenizer.into_iter().peekable(),
-            variable_scopes: vec![VariableScope::new()],
+            function: FunctionContext::new(
+                String::from("main"),
+                Type::function_returning(Type::Int, vec![], false),
+            ),
             last_label_suffix: 0,
+            break_labels: Vec::new(),
+            switch_contexts: Vec::new(),
+            functions: HashMap::new(),
+            defined_functions: HashSet::new(),
+            static_functions: HashSet::new(),
+            file_scope: VariableScope::new(0),
+            variables: Vec::new(),
+            first_dynamic_initializer_index: None,
+            warnings: Vec::new(),
         }
     }
 
-    pub fn parse(&mut self) -> Result<Node> {
+    pub fn take_warnings(&mut self) -> Vec<CompileWarning> {
+        std::mem::take(&mut self.warnings)
+    }
+
+    pub fn parse(&mut self) -> Result<Program> {
+        let mut functions = Vec::<FunctionDefinition>::new();
         let mut statements = Vec::<Node>::new();
+        let mut first_statement_index = None;
         while self.next_eof().is_none() {
-            statements.push(self.consume_statement()?);
+            if let Some(function) = self.consume_function_definition()? {
+                functions.push(function);
+                continue;
+            }
+
+            let index = self.current_index_in_text().unwrap_or(self.text.len());
+            let statement = self.consume_statement()?;
+            // declarations without initializers do not need main
+            if statement != (Node::Block { statements: vec![] }) {
+                first_statement_index.get_or_insert(index);
+            }
+            statements.push(statement);
+        }
+        let body = self.resolve_goto_targets(Node::Block { statements })?;
+
+        if !self.defined_functions.contains("main") {
+            functions.insert(
+                0,
+                FunctionDefinition {
+                    name: String::from("main"),
+                    parameters: vec![],
+                    is_variadic: false,
+                    is_static: false,
+                    stack_size: self.function.stack_size.div_ceil(16) * 16,
+                    body,
+                },
+            );
+        } else if let Some(index) = self.first_dynamic_initializer_index {
+            return Err(CompileError::not_a_constant(index));
+        } else if let Some(index) = first_statement_index {
+            return Err(CompileError::redefinition(index, String::from("main")));
         }
 
-        Ok(Node::Block {
-            statements: statements,
+        Ok(Program {
+            functions,
+            variables: std::mem::take(&mut self.variables),
         })
     }
 
@@ -64,17 +347,133 @@ impl<'a> Parser<'a> {
 
     fn error_unexpected_token(&mut self, expected: Vec<TokenKind>) -> CompileError {
         CompileError::unexpected_token(
-            self.current_index_in_text()
-                .unwrap_or_else(|| self.text.len()),
+            self.current_index_in_text().unwrap_or(self.text.len()),
             expected,
         )
     }
 
-    fn get_or_insert_offset_by_name(&mut self, identifier: String) -> usize {
-        self.variable_scopes
-            .last_mut()
+    fn enter_scope(&mut self) {
+        let last_offset = self.function.variable_scopes.last().unwrap().last_offset;
+        self.function
+            .variable_scopes
+            .push(VariableScope::new(last_offset));
+    }
+
+    // variable length arrays are released by restoring the stack pointer when their scope exits
+    fn leave_scope(&mut self) -> Option<Node> {
+        let scope = self.function.variable_scopes.pop().unwrap();
+        scope
+            .variable_arrays
+            .first()
+            .map(|&(_, offset)| Node::RestoreStackPointer { offset })
+    }
+
+    fn variable_arrays_in_scope(&self) -> Vec<(usize, usize)> {
+        self.function
+            .variable_scopes
+            .iter()
+            .flat_map(|scope| scope.variable_arrays.iter().copied())
+            .collect()
+    }
+
+    fn declare_variable(&mut self, identifier: String, ty: Type, index: usize) -> Result<Variable> {
+        let scope = self.function.variable_scopes.last_mut().unwrap();
+        let variable = match ty {
+            Type::VariableArray { .. } => scope.insert_variable_array(identifier.to_owned(), ty),
+            ty => scope.insert_variable(identifier.to_owned(), ty),
+        }
+        .ok_or_else(|| CompileError::redefinition(index, identifier))?;
+        // including the room below over-aligned variables
+        self.function.stack_size = self.function.stack_size.max(scope.last_offset);
+
+        Ok(variable)
+    }
+
+    // hidden locals are named so that they cannot collide with variables
+    fn declare_hidden_variable(&mut self, name: &str, ty: Type) -> Node {
+        let identifier = format!(".L{}.{}", name, self.get_next_label_suffix());
+        self.declare_variable(identifier.to_owned(), ty, 0)
             .unwrap()
-            .get_or_insert_offset_by_name(identifier)
+            .into_node(identifier)
+    }
+
+    // declarations without prototypes do not replace earlier ones with them
+    fn declare_function(&mut self, identifier: String, ty: Type, index: usize) -> Result {
+        let has_prototype = |ty: &Type| {
+            matches!(
+                ty,
+                Type::Function {
+                    has_prototype: true,
+                    ..
+                }
+            )
+        };
+        // functions share the namespace of variables at file scope
+        if self.file_scope.variables.contains_key(&identifier) {
+            return Err(CompileError::redefinition(index, identifier));
+        }
+        if let Some(declared_ty) = self.functions.get(&identifier) {
+            if !declared_ty.is_compatible(&ty) {
+                return Err(CompileError::conflicting_types(index, identifier));
+            }
+        }
+        if has_prototype(&ty) || !self.functions.get(&identifier).is_some_and(has_prototype) {
+            self.functions.insert(identifier, ty);
+        }
+
+        Ok(())
+    }
+
+    // statements outside of function definitions are in the outermost scope of main
+    fn is_file_scope(&self) -> bool {
+        self.function.variable_scopes.len() == 1
+    }
+
+    fn current_scope_mut(&mut self) -> &mut VariableScope {
+        if self.is_file_scope() {
+            &mut self.file_scope
+        } else {
+            self.function.variable_scopes.last_mut().unwrap()
+        }
+    }
+
+    fn get_tag_by_name(&self, tag: &str) -> Option<Type> {
+        self.function
+            .variable_scopes
+            .iter()
+            .rev()
+            .chain(std::iter::once(&self.file_scope))
+            .find_map(|scope| scope.tags.get(tag))
+            .cloned()
+    }
+
+    fn get_variable_by_name(&self, identifier: &str) -> Option<Variable> {
+        self.function
+            .variable_scopes
+            .iter()
+            .rev()
+            .chain(std::iter::once(&self.file_scope))
+            .find_map(|scope| scope.get_variable_by_name(identifier))
+            .cloned()
+    }
+
+    // undeclared variables are int and live in the outermost scope
+    // the slot is taken below every open scope so that none of them reuses it
+    fn insert_implicit_variable(&mut self, identifier: String) -> Variable {
+        let offset = self.function.variable_scopes.last().unwrap().last_offset + 8;
+        for scope in self.function.variable_scopes.iter_mut() {
+            scope.last_offset = offset;
+        }
+        self.function.stack_size = self.function.stack_size.max(offset);
+        let variable = Variable::Local {
+            offset,
+            ty: Type::Int,
+        };
+        self.function.variable_scopes[0]
+            .variables
+            .insert(identifier, variable.to_owned());
+
+        variable
     }
 
     fn get_next_label_suffix(&mut self) -> usize {
@@ -82,34 +481,125 @@ impl<'a> Parser<'a> {
         self.last_label_suffix
     }
 
+    // user labels are mangled not to collide with labels generated for control flow
+    // or with the same labels in other functions
+    fn get_user_label(&self, name: &str) -> String {
+        format!(".Luser.{}.{}", self.function.name, name)
+    }
+
+    // gotos that may leave variable length arrays jump to code placed after the body
+    // which restores the stack pointer to where it was before the arrays were allocated
+    fn resolve_goto_targets(&mut self, body: Node) -> Result<Node> {
+        let mut statements = Vec::new();
+        for target in std::mem::take(&mut self.function.goto_targets) {
+            let Some(variable_arrays) = self.function.defined_labels.get(&target.name) else {
+                return Err(CompileError::undefined_label(target.index, target.name));
+            };
+            // jumps may leave the scopes of variable length arrays but not enter them
+            if !target.variable_arrays.starts_with(variable_arrays) {
+                return Err(CompileError::jump_into_variable_array_scope(
+                    target.index,
+                    target.name,
+                ));
+            }
+            if target.variable_arrays.is_empty() {
+                continue;
+            }
+            let mut restore = Vec::new();
+            if let Some(&(_, offset)) = target.variable_arrays.get(variable_arrays.len()) {
+                restore.push(Node::RestoreStackPointer { offset });
+            }
+            restore.push(Node::Goto {
+                label: self.get_user_label(&target.name),
+            });
+            statements.push(Node::Label {
+                label: target.label,
+                statement: Node::Block {
+                    statements: restore,
+                }
+                .into(),
+            });
+        }
+        self.function.defined_labels.clear();
+        if statements.is_empty() {
+            return Ok(body);
+        }
+
+        let end_label = format!(".Lend{}", self.get_next_label_suffix());
+        statements.insert(0, body);
+        statements.insert(
+            1,
+            Node::Goto {
+                label: end_label.to_owned(),
+            },
+        );
+        statements.push(Node::Label {
+            label: end_label,
+            statement: Node::Block { statements: vec![] }.into(),
+        });
+        Ok(Node::Block { statements })
+    }
+
+    fn consume_breakable_statement(&mut self, end_label: &str) -> Result<Node> {
+        self.break_labels
+            .push((end_label.to_owned(), self.function.variable_scopes.len()));
+        let statement = self.consume_statement();
+        self.break_labels.pop();
+
+        statement
+    }
+
     fn consume_statement(&mut self) -> Result<Node> {
+        let index = self.current_index_in_text().unwrap_or(self.text.len());
+
         if self.next_symbol_curly_bracket_left().is_some() {
+            self.enter_scope();
             let mut statements = Vec::<Node>::new();
-            loop {
-                if let Ok(statement) = self.consume_statement() {
-                    statements.push(statement);
-                } else {
-                    break;
+            while self.next_symbol_curly_bracket_right().is_none() {
+                if self.next_eof().is_some() {
+                    return Err(CompileError::unexpected_eof(self.text.len()));
                 }
+                statements.push(self.consume_statement()?);
             }
-            if self.next_symbol_curly_bracket_right().is_none() {
-                return Err(self.error_unexpected_token(vec![TokenKind::SymbolCurlyBracketRight]));
-            }
+            statements.extend(self.leave_scope());
 
             Ok(Node::Block { statements })
+        } else if let Some((storage_class, is_thread_local, ty)) =
+            self.consume_declaration_specifiers()?
+        {
+            // declarations of struct tags may have no declarators
+            if self.next_symbol_semicolon().is_some() {
+                return Ok(Node::Block { statements: vec![] });
+            }
+            self.consume_declaration(storage_class, is_thread_local, ty)
         } else if self.next_keyword_return().is_some() {
-            let expression = self.consume_expression()?;
+            if self.function.return_ty == Type::Void && self.next_symbol_semicolon().is_some() {
+                return Ok(Node::Return { value: None });
+            }
+            let value_index = self.current_index_in_text().unwrap_or(self.text.len());
+            let value = self.consume_expression()?;
+            if self.function.return_ty != Type::Void {
+                Self::check_assignment(&value, &self.function.return_ty, value_index)?;
+            }
+            let mut expression = Self::convert(value, &self.function.return_ty);
+            if let Some(pointer) = &self.function.return_pointer {
+                let target = Node::Dereference {
+                    value: pointer.to_owned().into(),
+                };
+                expression = Self::new_assign(target, expression);
+            }
             if self.next_symbol_semicolon().is_none() {
                 return Err(self.error_unexpected_token(vec![TokenKind::SymbolSemicolon]));
             }
             Ok(Node::Return {
-                value: expression.into(),
+                value: Some(expression.into()),
             })
         } else if self.next_keyword_if().is_some() {
             if self.next_symbol_round_bracket_left().is_none() {
                 return Err(self.error_unexpected_token(vec![TokenKind::SymbolRoundBracketLeft]));
             }
-            let condition = self.consume_expression()?;
+            let condition_index = self.current_index_in_text().unwrap_or(self.text.len());
+            let condition = Self::new_condition(self.consume_expression()?, condition_index)?;
             if self.next_symbol_round_bracket_right().is_none() {
                 return Err(self.error_unexpected_token(vec![TokenKind::SymbolRoundBracketLeft]));
             }
@@ -137,28 +627,41 @@ impl<'a> Parser<'a> {
             if self.next_symbol_round_bracket_left().is_none() {
                 return Err(self.error_unexpected_token(vec![TokenKind::SymbolRoundBracketLeft]));
             }
-            let condition = self.consume_expression()?;
+            let condition_index = self.current_index_in_text().unwrap_or(self.text.len());
+            let condition = Self::new_condition(self.consume_expression()?, condition_index)?;
             if self.next_symbol_round_bracket_right().is_none() {
                 return Err(self.error_unexpected_token(vec![TokenKind::SymbolRoundBracketLeft]));
             }
-            let statement = self.consume_statement()?;
             let label_suffix = self.get_next_label_suffix();
+            let end_label = format!(".Lend{}", label_suffix);
+            let statement = self.consume_breakable_statement(&end_label)?;
 
             Ok(Node::While {
                 condition: condition.into(),
                 statement: statement.into(),
                 begin_label: format!(".Lbegin{}", label_suffix),
-                end_label: format!(".Lend{}", label_suffix),
+                end_label,
             })
         } else if self.next_keyword_for().is_some() {
             if self.next_symbol_round_bracket_left().is_none() {
                 return Err(self.error_unexpected_token(vec![TokenKind::SymbolRoundBracketLeft]));
             }
-            let initializer = self.consume_expression();
-            if self.next_symbol_semicolon().is_none() {
-                return Err(self.error_unexpected_token(vec![TokenKind::SymbolSemicolon]));
-            }
-            let condition = self.consume_expression();
+            self.enter_scope();
+            let initializer = if let Some(ty) = self.consume_type_specifier()? {
+                Some(self.consume_declaration(None, false, ty)?)
+            } else {
+                let initializer = self.consume_expression().ok();
+                if self.next_symbol_semicolon().is_none() {
+                    return Err(self.error_unexpected_token(vec![TokenKind::SymbolSemicolon]));
+                }
+                initializer
+            };
+            let condition_index = self.current_index_in_text().unwrap_or(self.text.len());
+            let condition = self
+                .consume_expression()
+                .ok()
+                .map(|condition| Self::new_condition(condition, condition_index))
+                .transpose()?;
             if self.next_symbol_semicolon().is_none() {
                 return Err(self.error_unexpected_token(vec![TokenKind::SymbolSemicolon]));
             }
@@ -166,16 +669,172 @@ impl<'a> Parser<'a> {
             if self.next_symbol_round_bracket_right().is_none() {
                 return Err(self.error_unexpected_token(vec![TokenKind::SymbolRoundBracketLeft]));
             }
-            let statement = self.consume_statement()?;
             let label_suffix = self.get_next_label_suffix();
+            let end_label = format!(".Lend{}", label_suffix);
+            let statement = self.consume_breakable_statement(&end_label)?;
+            let restore = self.leave_scope();
 
-            Ok(Node::For {
-                initializer: initializer.ok().map(|v| v.into()),
-                condition: condition.ok().map(|v| v.into()),
+            let node = Node::For {
+                initializer: initializer.map(|v| v.into()),
+                condition: condition.map(|v| v.into()),
                 updater: updater.ok().map(|v| v.into()),
                 statement: statement.into(),
                 begin_label: format!(".Lbegin{}", label_suffix),
-                end_label: format!(".Lend{}", label_suffix),
+                end_label,
+            };
+            match restore {
+                Some(restore) => Ok(Node::Block {
+                    statements: vec![node, restore],
+                }),
+                None => Ok(node),
+            }
+        } else if self.next_keyword_switch().is_some() {
+            if self.next_symbol_round_bracket_left().is_none() {
+                return Err(self.error_unexpected_token(vec![TokenKind::SymbolRoundBracketLeft]));
+            }
+            let condition_index = self.current_index_in_text().unwrap_or(self.text.len());
+            let condition = self.consume_expression()?;
+            if !condition.ty().is_integer() {
+                return Err(CompileError::not_an_integer(condition_index));
+            }
+            let ty = Self::promoted_ty(&condition);
+            let condition = Self::convert(condition, &ty);
+            if self.next_symbol_round_bracket_right().is_none() {
+                return Err(self.error_unexpected_token(vec![TokenKind::SymbolRoundBracketRight]));
+            }
+            let label_suffix = self.get_next_label_suffix();
+            let end_label = format!(".Lend{}", label_suffix);
+            let variable_arrays = self.variable_arrays_in_scope();
+            self.switch_contexts
+                .push(SwitchContext::new(label_suffix, ty, variable_arrays));
+            let statement = self.consume_breakable_statement(&end_label);
+            let context = self.switch_contexts.pop().unwrap();
+
+            Ok(Node::Switch {
+                condition: condition.into(),
+                statement: statement?.into(),
+                cases: context.cases,
+                default_label: context.default_label,
+                end_label,
+            })
+        } else if self.next_keyword_case().is_some() {
+            let value_index = self.current_index_in_text().unwrap_or(self.text.len());
+            let value = self.consume_expression()?;
+            let value = Some(value)
+                .filter(|value| value.ty().is_integer())
+                .and_then(|value| value.evaluate_long_constant())
+                .ok_or_else(|| CompileError::not_a_constant(value_index))?;
+            if self.next_symbol_colon().is_none() {
+                return Err(self.error_unexpected_token(vec![TokenKind::SymbolColon]));
+            }
+
+            let variable_arrays = self.variable_arrays_in_scope();
+            let context = self
+                .switch_contexts
+                .last_mut()
+                .ok_or_else(|| CompileError::case_outside_of_switch(index))?;
+            if context.variable_arrays != variable_arrays {
+                return Err(CompileError::switch_into_variable_array_scope(index));
+            }
+            let value = context.ty.convert_constant(value);
+            if context.cases.iter().any(|case| case.value == value) {
+                return Err(CompileError::duplicate_case_value(value_index, value));
+            }
+            let label = format!(".Lcase{}_{}", context.label_suffix, context.cases.len());
+            context.cases.push(SwitchCase {
+                value,
+                label: label.to_owned(),
+            });
+
+            let statement = self.consume_statement()?;
+            Ok(Node::Label {
+                label,
+                statement: statement.into(),
+            })
+        } else if self.next_keyword_default().is_some() {
+            if self.next_symbol_colon().is_none() {
+                return Err(self.error_unexpected_token(vec![TokenKind::SymbolColon]));
+            }
+
+            let variable_arrays = self.variable_arrays_in_scope();
+            let context = self
+                .switch_contexts
+                .last_mut()
+                .ok_or_else(|| CompileError::case_outside_of_switch(index))?;
+            if context.variable_arrays != variable_arrays {
+                return Err(CompileError::switch_into_variable_array_scope(index));
+            }
+            if context.default_label.is_some() {
+                return Err(CompileError::duplicate_default(index));
+            }
+            let label = format!(".Ldefault{}", context.label_suffix);
+            context.default_label = Some(label.to_owned());
+
+            let statement = self.consume_statement()?;
+            Ok(Node::Label {
+                label,
+                statement: statement.into(),
+            })
+        } else if self.next_keyword_break().is_some() {
+            if self.next_symbol_semicolon().is_none() {
+                return Err(self.error_unexpected_token(vec![TokenKind::SymbolSemicolon]));
+            }
+            let (end_label, scope_count) = self
+                .break_labels
+                .last()
+                .ok_or_else(|| CompileError::break_outside_of_loop(index))?;
+            let node = Node::Break {
+                end_label: end_label.to_owned(),
+            };
+
+            // variable length arrays in the scopes being exited are released
+            match self.function.variable_scopes[*scope_count..]
+                .iter()
+                .find_map(|scope| scope.variable_arrays.first())
+            {
+                Some(&(_, offset)) => Ok(Node::Block {
+                    statements: vec![Node::RestoreStackPointer { offset }, node],
+                }),
+                None => Ok(node),
+            }
+        } else if self.next_keyword_goto().is_some() {
+            let target_index = self.current_index_in_text().unwrap_or(self.text.len());
+            let name = self.next_identifier().ok_or_else(|| {
+                self.error_unexpected_token(vec![TokenKind::Identifier(String::new())])
+            })?;
+            if self.next_symbol_semicolon().is_none() {
+                return Err(self.error_unexpected_token(vec![TokenKind::SymbolSemicolon]));
+            }
+            let variable_arrays = self.variable_arrays_in_scope();
+            let label = if variable_arrays.is_empty() {
+                self.get_user_label(&name)
+            } else {
+                format!(".Lgoto.{}", self.get_next_label_suffix())
+            };
+            self.function.goto_targets.push(GotoTarget {
+                name,
+                index: target_index,
+                label: label.to_owned(),
+                variable_arrays,
+            });
+
+            Ok(Node::Goto { label })
+        } else if let Some(name) = self.next_label() {
+            let label = self.get_user_label(&name);
+            let variable_arrays = self.variable_arrays_in_scope();
+            if self
+                .function
+                .defined_labels
+                .insert(name.to_owned(), variable_arrays)
+                .is_some()
+            {
+                return Err(CompileError::duplicate_label(index, name));
+            }
+
+            let statement = self.consume_statement()?;
+            Ok(Node::Label {
+                label,
+                statement: statement.into(),
             })
         } else {
             let expression = self.consume_expression()?;
@@ -186,762 +845,4615 @@ impl<'a> Parser<'a> {
         }
     }
 
-    fn consume_expression(&mut self) -> Result<Node> {
-        self.consume_assign()
-    }
-
-    fn consume_assign(&mut self) -> Result<Node> {
-        let index = self
-            .current_index_in_text()
-            .unwrap_or_else(|| self.text.len());
-        let equality = self.consume_equality()?;
+    // a function definition is a declarator of function type followed by a body
+    // anything else is left to be parsed again as a statement
+    fn consume_function_definition(&mut self) -> Result<Option<FunctionDefinition>> {
+        // struct tags defined in the specifiers are defined again when parsed as a declaration
+        let (tokens, tags) = (self.tokens.clone(), self.file_scope.tags.clone());
+        let (storage_class, is_thread_local, declarator) =
+            match self.consume_declaration_specifiers()? {
+                Some((storage_class, is_thread_local, ty)) => {
+                    (storage_class, is_thread_local, self.consume_declarator(ty)?)
+                }
+                None => return Ok(None),
+            };
+        let is_definition = declarator.ty.is_function()
+            && self
+                .tokens
+                .peek()
+                .is_some_and(|token| token.kind == TokenKind::SymbolCurlyBracketLeft);
+        if !is_definition {
+            (self.tokens, self.file_scope.tags) = (tokens, tags);
+            return Ok(None);
+        }
 
-        if self.next_symbol_equal().is_some() {
-            if !equality.is_left_value() {
-                return Err(CompileError::not_a_left_value(index));
-            }
+        let Declarator {
+            identifier,
+            index,
+            ty,
+            parameters,
+        } = declarator;
+        let name = identifier.ok_or_else(|| {
+            CompileError::unexpected_token(index, vec![TokenKind::Identifier(String::new())])
+        })?;
+        if is_thread_local {
+            return Err(CompileError::invalid_storage_class(index));
+        }
+        // parameters and return values of definitions are objects, except for void returns
+        let Type::Function { return_ty, .. } = &ty else {
+            unreachable!("definitions are of functions")
+        };
+        if !return_ty.is_complete() && **return_ty != Type::Void {
+            return Err(Self::incomplete_type_error(return_ty, index));
+        }
+        if let Some(parameter) = parameters
+            .iter()
+            .find(|parameter| !parameter.ty.is_complete())
+        {
+            return Err(Self::incomplete_type_error(&parameter.ty, parameter.index));
+        }
+        if !self.defined_functions.insert(name.to_owned()) {
+            return Err(CompileError::redefinition(index, name));
+        }
+        self.declare_function(name.to_owned(), ty.to_owned(), index)?;
+        if storage_class == Some(StorageClass::Static) {
+            self.static_functions.insert(name.to_owned());
+        }
 
-            let assign = self.consume_assign()?;
-            Ok(Node::OperatorAssign {
-                lhs: equality.into(),
-                rhs: assign.into(),
-            })
-        } else {
-            Ok(equality)
+        let outer_function = std::mem::replace(
+            &mut self.function,
+            FunctionContext::new(name.to_owned(), ty),
+        );
+        let mut parameter_nodes = Vec::<Node>::new();
+        let return_ty = self.function.return_ty.to_owned();
+        if return_ty.is_aggregate() && eightbyte_classes(&return_ty).is_none() {
+            let identifier = String::from(".Lreturn");
+            let ty = Type::pointer_to(return_ty);
+            let variable = self.declare_variable(identifier.to_owned(), ty.to_owned(), index)?;
+            let node = variable.into_node(identifier);
+            self.function.parameter_types.insert(0, ty);
+            self.function.return_pointer = Some(node.to_owned());
+            parameter_nodes.push(node);
         }
-    }
+        for parameter in parameters {
+            let identifier = parameter.identifier.ok_or_else(|| {
+                CompileError::unexpected_token(
+                    parameter.index,
+                    vec![TokenKind::Identifier(String::new())],
+                )
+            })?;
+            let variable =
+                self.declare_variable(identifier.to_owned(), parameter.ty, parameter.index)?;
+            parameter_nodes.push(variable.into_node(identifier));
+        }
+        let body = self.consume_statement()?;
+        let body = self.resolve_goto_targets(body)?;
+        let function = std::mem::replace(&mut self.function, outer_function);
 
-    fn consume_equality(&mut self) -> Result<Node> {
-        let mut node = self.consume_relational()?;
+        Ok(Some(FunctionDefinition {
+            is_static: self.static_functions.contains(&name),
+            name,
+            parameters: parameter_nodes,
+            is_variadic: function.is_variadic,
+            stack_size: function.stack_size.div_ceil(16) * 16,
+            body,
+        }))
+    }
 
+    // consumes declarators following a type and the terminating semicolon
+    // initializers of locals are turned into stores to the declared variables
+    // variables are declared before their initializers so that those can refer to them
+    // thread-local variables are only declared at file scope or along with static or extern
+    fn consume_declaration(
+        &mut self,
+        storage_class: Option<StorageClass>,
+        is_thread_local: bool,
+        base_ty: Type,
+    ) -> Result<Node> {
+        let mut statements = Vec::<Node>::new();
         loop {
-            node = if self.next_symbol_double_equal().is_some() {
-                Node::OperatorEq {
-                    lhs: node.into(),
-                    rhs: self.consume_relational()?.into(),
+            let Declarator {
+                identifier,
+                index,
+                mut ty,
+                ..
+            } = self.consume_declarator(base_ty.to_owned())?;
+            let identifier = identifier.ok_or_else(|| {
+                CompileError::unexpected_token(index, vec![TokenKind::Identifier(String::new())])
+            })?;
+            if is_thread_local
+                && (ty.is_function() || storage_class.is_none() && !self.is_file_scope())
+            {
+                return Err(CompileError::invalid_storage_class(index));
+            }
+            if ty.is_function() {
+                if storage_class == Some(StorageClass::Static) {
+                    self.static_functions.insert(identifier.to_owned());
                 }
-            } else if self.next_symbol_exclamation_and_equal().is_some() {
-                Node::OperatorNe {
-                    lhs: node.into(),
-                    rhs: self.consume_relational()?.into(),
+                self.declare_function(identifier, ty, index)?;
+                if self.next_symbol_comma().is_none() {
+                    break;
                 }
-            } else {
-                break Ok(node);
+                continue;
+            }
+            // sizes of variable length arrays are computed where they are declared
+            // which only objects in blocks can be
+            statements.append(&mut self.function.variable_array_sizes);
+            if ty.is_variably_modified() && storage_class.is_some() {
+                return Err(CompileError::not_a_constant(index));
+            }
+            if let Type::VariableArray { .. } = ty {
+                self.declare_variable_array(identifier, ty, index, &mut statements)?;
+                if self.next_symbol_comma().is_none() {
+                    break;
+                }
+                continue;
             }
-        }
-    }
 
-    fn consume_relational(&mut self) -> Result<Node> {
-        let mut node = self.consume_add()?;
+            let has_initializer = self
+                .tokens
+                .peek()
+                .is_some_and(|token| token.kind == TokenKind::SymbolEqual);
+            let is_unknown_length = matches!(ty, Type::Array { length: None, .. });
+            // objects declared extern may be defined with complete types elsewhere
+            // which void never is
+            if !ty.is_complete()
+                && (storage_class != Some(StorageClass::Extern) || *ty.unqualified() == Type::Void)
+                && !(has_initializer && is_unknown_length)
+            {
+                return Err(Self::incomplete_type_error(&ty, index));
+            }
 
-        loop {
-            node = if self.next_symbol_angle_bracket_left().is_some() {
-                Node::OperatorLt {
-                    lhs: node.into(),
-                    rhs: self.consume_add()?.into(),
+            match storage_class {
+                Some(StorageClass::Extern) if !has_initializer => {
+                    self.declare_extern_variable(identifier, ty, is_thread_local, index)?
                 }
-            } else if self.next_symbol_angle_bracket_right().is_some() {
-                Node::OperatorLt {
-                    lhs: self.consume_add()?.into(),
-                    rhs: node.into(),
+                Some(StorageClass::Extern) if !self.is_file_scope() => {
+                    return Err(self.error_unexpected_token(vec![TokenKind::SymbolSemicolon]));
                 }
-            } else if self.next_symbol_angle_bracket_left_and_equal().is_some() {
-                Node::OperatorLtEq {
-                    lhs: node.into(),
-                    rhs: self.consume_add()?.into(),
+                _ if self.is_file_scope() => {
+                    let is_static = storage_class == Some(StorageClass::Static);
+                    let ty =
+                        self.declare_global_variable(&identifier, ty, is_thread_local, index)?;
+                    let (ty, initializer) =
+                        self.consume_declaration_initializer(&identifier, ty, index)?;
+                    self.define_global_variable(
+                        identifier,
+                        ty,
+                        is_static,
+                        is_thread_local,
+                        initializer,
+                        &mut statements,
+                    )?;
                 }
-            } else if self.next_symbol_angle_bracket_right_and_equal().is_some() {
-                Node::OperatorLtEq {
-                    lhs: self.consume_add()?.into(),
-                    rhs: node.into(),
+                Some(StorageClass::Static) => {
+                    let label = self.declare_static_local_variable(
+                        &identifier,
+                        &ty,
+                        is_thread_local,
+                        index,
+                    )?;
+                    let (ty, initializer) =
+                        self.consume_declaration_initializer(&identifier, ty, index)?;
+                    self.define_static_local_variable(label, ty, is_thread_local, initializer)?;
                 }
-            } else {
-                break Ok(node);
+                _ => {
+                    // the slots of arrays of unknown length depend on their initializers
+                    // so those are only declared once their initializers are consumed
+                    let variable = if is_unknown_length {
+                        None
+                    } else {
+                        Some(self.declare_variable(identifier.to_owned(), ty.to_owned(), index)?)
+                    };
+                    let initializer = self.consume_optional_initializer(&ty)?;
+                    if let Some((_, initializer)) = &initializer {
+                        ty = Self::complete_type(ty, initializer);
+                    }
+                    if !ty.is_complete() {
+                        return Err(Self::incomplete_type_error(&ty, index));
+                    }
+                    let variable = match variable {
+                        Some(variable) => variable,
+                        None => self.declare_variable(identifier.to_owned(), ty, index)?,
+                    };
+                    if let Some((_, initializer)) = initializer {
+                        let target = variable.into_node(identifier);
+                        if target.ty().is_aggregate() {
+                            statements.push(Node::ZeroFill {
+                                target: target.to_owned().into(),
+                            });
+                        }
+                        Self::new_initialization(target, initializer, &mut statements);
+                    }
+                }
+            }
+
+            if self.next_symbol_comma().is_none() {
+                break;
             }
         }
+        if self.next_symbol_semicolon().is_none() {
+            return Err(self.error_unexpected_token(vec![TokenKind::SymbolSemicolon]));
+        }
+
+        Ok(Node::Block { statements })
     }
 
-    fn consume_add(&mut self) -> Result<Node> {
-        let mut node = self.consume_mul()?;
+    // variable length arrays cannot be initialized and are allocated below the fixed locals
+    // once the stack pointer to restore when their scope exits has been saved
+    fn declare_variable_array(
+        &mut self,
+        identifier: String,
+        ty: Type,
+        index: usize,
+        statements: &mut Vec<Node>,
+    ) -> Result {
+        if self
+            .tokens
+            .peek()
+            .is_some_and(|token| token.kind == TokenKind::SymbolEqual)
+        {
+            return Err(self.error_unexpected_token(vec![TokenKind::SymbolSemicolon]));
+        }
+        // the stack pointer is kept for each array so that gotos can release some of them
+        let Node::LocalVariable {
+            offset: stack_pointer_offset,
+            ..
+        } = self.declare_hidden_variable("stack_pointer", Type::Long)
+        else {
+            unreachable!("hidden variables are locals")
+        };
+        statements.push(Node::SaveStackPointer {
+            offset: stack_pointer_offset,
+        });
 
-        loop {
-            node = if self.next_symbol_plus().is_some() {
-                Node::OperatorAdd {
-                    lhs: node.into(),
-                    rhs: self.consume_mul()?.into(),
-                }
-            } else if self.next_symbol_minus().is_some() {
-                Node::OperatorSub {
-                    lhs: node.into(),
-                    rhs: self.consume_mul()?.into(),
-                }
-            } else {
-                break Ok(node);
+        let size = Self::convert(Self::size_of(&ty), &Type::UnsignedLong);
+        let Node::Dereference { value: pointer } = self
+            .declare_variable(identifier.to_owned(), ty, index)?
+            .into_node(identifier)
+        else {
+            unreachable!("variable length arrays are accessed through pointers")
+        };
+        statements.push(Self::new_assign(
+            *pointer,
+            Node::Alloca { size: size.into() },
+        ));
+        let id = self.get_next_label_suffix();
+        self.function
+            .variable_scopes
+            .last_mut()
+            .unwrap()
+            .variable_arrays
+            .push((id, stack_pointer_offset));
+
+        Ok(())
+    }
+
+    // extern declarations refer to variables defined elsewhere, possibly in other objects
+    fn declare_extern_variable(
+        &mut self,
+        identifier: String,
+        ty: Type,
+        is_thread_local: bool,
+        index: usize,
+    ) -> Result {
+        if self.is_file_scope() && self.functions.contains_key(&identifier) {
+            return Err(CompileError::redefinition(index, identifier));
+        }
+        let scope = self.current_scope_mut();
+        match scope.variables.get_mut(&identifier) {
+            Some(Variable::Global {
+                label,
+                ty: declared_ty,
+                is_thread_local: was_thread_local,
+            }) if *label == identifier && *was_thread_local == is_thread_local => {
+                *declared_ty = declared_ty
+                    .redeclared(&ty)
+                    .ok_or_else(|| CompileError::redefinition(index, identifier))?;
+                Ok(())
+            }
+            Some(_) => Err(CompileError::redefinition(index, identifier)),
+            None => {
+                scope.insert_global(identifier.to_owned(), identifier, ty, is_thread_local);
+                Ok(())
             }
         }
     }
 
-    fn consume_mul(&mut self) -> Result<Node> {
-        let mut node = self.consume_unary()?;
+    // initializers of globals complete the types declared before them
+    fn consume_declaration_initializer(
+        &mut self,
+        identifier: &str,
+        mut ty: Type,
+        index: usize,
+    ) -> Result<(Type, Option<(usize, Initializer)>)> {
+        let initializer = self.consume_optional_initializer(&ty)?;
+        if let Some((_, initializer)) = &initializer {
+            ty = Self::complete_type(ty, initializer);
+            self.current_scope_mut()
+                .complete_global(identifier, ty.to_owned());
+        }
+        if !ty.is_complete() {
+            return Err(Self::incomplete_type_error(&ty, index));
+        }
 
-        loop {
-            node = if self.next_symbol_star().is_some() {
-                Node::OperatorMul {
-                    lhs: node.into(),
-                    rhs: self.consume_unary()?.into(),
+        Ok((ty, initializer))
+    }
+
+    // globals are labeled with their names and may have been declared extern before
+    // the declared type to define them with is returned
+    fn declare_global_variable(
+        &mut self,
+        identifier: &str,
+        ty: Type,
+        is_thread_local: bool,
+        index: usize,
+    ) -> Result<Type> {
+        let is_defined = self
+            .variables
+            .iter()
+            .any(|variable| variable.label == identifier);
+        let redefinition = || CompileError::redefinition(index, identifier.to_owned());
+        if self.functions.contains_key(identifier) {
+            return Err(redefinition());
+        }
+        match self.file_scope.variables.get_mut(identifier) {
+            Some(Variable::Global {
+                ty: declared_ty,
+                is_thread_local: was_thread_local,
+                ..
+            }) if !is_defined && *was_thread_local == is_thread_local => {
+                *declared_ty = declared_ty.redeclared(&ty).ok_or_else(redefinition)?;
+                Ok(declared_ty.to_owned())
+            }
+            Some(_) => Err(redefinition()),
+            None => {
+                self.file_scope.insert_global(
+                    identifier.to_owned(),
+                    identifier.to_owned(),
+                    ty.to_owned(),
+                    is_thread_local,
+                );
+                Ok(ty)
+            }
+        }
+    }
+
+    // initializers that are not constant are left to the implicit main to run
+    // except for those of thread-local variables, which main cannot run for every thread
+    fn define_global_variable(
+        &mut self,
+        identifier: String,
+        ty: Type,
+        is_static: bool,
+        is_thread_local: bool,
+        initializer: Option<(usize, Initializer)>,
+        statements: &mut Vec<Node>,
+    ) -> Result {
+        let (mut data, mut relocations) = (None, Vec::new());
+        if let Some((initializer_index, initializer)) = initializer {
+            match Self::evaluate_initializer_data(&initializer, &ty) {
+                Some((bytes, addresses)) => (data, relocations) = (Some(bytes), addresses),
+                None if is_thread_local => {
+                    return Err(CompileError::not_a_constant(initializer_index))
                 }
-            } else if self.next_symbol_slash().is_some() {
-                Node::OperatorDiv {
-                    lhs: node.into(),
-                    rhs: self.consume_unary()?.into(),
+                None => {
+                    self.first_dynamic_initializer_index
+                        .get_or_insert(initializer_index);
+                    let variable = self.file_scope.get_variable_by_name(&identifier).unwrap();
+                    let target = variable.to_owned().into_node(identifier.to_owned());
+                    Self::new_initialization(target, initializer, statements);
                 }
-            } else {
-                break Ok(node);
             }
         }
+        self.variables.push(VariableDefinition {
+            label: identifier,
+            ty,
+            is_static,
+            is_thread_local,
+            data,
+            relocations,
+        });
+
+        Ok(())
     }
 
-    fn consume_unary(&mut self) -> Result<Node> {
-        if self.next_symbol_plus().is_some() {
-            return self.consume_primary();
-        } else if self.next_symbol_minus().is_some() {
-            let rhs = self.consume_primary()?;
-            return Ok(Node::OperatorSub {
-                lhs: Node::Integer { value: 0 }.into(),
-                rhs: rhs.into(),
-            });
-        }
+    // static locals are globals only visible in their scope
+    // their labels are uniquified as any function may have one of the same name
+    fn declare_static_local_variable(
+        &mut self,
+        identifier: &str,
+        ty: &Type,
+        is_thread_local: bool,
+        index: usize,
+    ) -> Result<String> {
+        let label_suffix = self.get_next_label_suffix();
+        let label = format!(
+            ".Lstatic.{}.{}.{}",
+            self.function.name, identifier, label_suffix
+        );
+        self.function
+            .variable_scopes
+            .last_mut()
+            .unwrap()
+            .insert_global(
+                identifier.to_owned(),
+                label.to_owned(),
+                ty.to_owned(),
+                is_thread_local,
+            )
+            .ok_or_else(|| CompileError::redefinition(index, identifier.to_owned()))?;
 
-        self.consume_primary()
+        Ok(label)
     }
 
-    fn consume_primary(&mut self) -> Result<Node> {
-        if let Some(value) = self.next_numeric_value() {
-            Ok(Node::Integer { value })
-        } else if let Some(value) = self.next_identifier() {
-            if self.next_symbol_round_bracket_left().is_some() {
-                let mut arguments = Vec::<Node>::new();
-                if let Ok(argument) = self.consume_expression() {
-                    arguments.push(argument);
-                    loop {
-                        if self.next_symbol_comma().is_none() {
-                            break;
-                        }
-                        arguments.push(self.consume_expression()?);
-                    }
-                }
+    fn define_static_local_variable(
+        &mut self,
+        label: String,
+        ty: Type,
+        is_thread_local: bool,
+        initializer: Option<(usize, Initializer)>,
+    ) -> Result {
+        let (mut data, mut relocations) = (None, Vec::new());
+        if let Some((initializer_index, initializer)) = initializer {
+            let (bytes, addresses) = Self::evaluate_initializer_data(&initializer, &ty)
+                .ok_or_else(|| CompileError::not_a_constant(initializer_index))?;
+            (data, relocations) = (Some(bytes), addresses);
+        }
+        self.variables.push(VariableDefinition {
+            label,
+            ty,
+            is_static: true,
+            is_thread_local,
+            data,
+            relocations,
+        });
 
-                if self.next_symbol_round_bracket_right().is_none() {
-                    return Err(
-                        self.error_unexpected_token(vec![TokenKind::SymbolRoundBracketRight])
-                    );
-                }
+        Ok(())
+    }
 
-                Ok(Node::FunctionCall {
-                    identifier: value,
-                    arguments,
-                })
-            } else {
-                let offset = self.get_or_insert_offset_by_name(value.to_owned());
-                Ok(Node::LocalVariable {
-                    identifier: value,
-                    offset,
-                })
-            }
-        } else if self.next_symbol_round_bracket_left().is_some() {
-            let node = self.consume_expression()?;
-            self.next_symbol_round_bracket_right().ok_or_else(|| {
-                self.error_unexpected_token(vec![TokenKind::SymbolRoundBracketRight])
-            })?;
-            Ok(node)
-        } else {
-            Err(self.error_unexpected_token(vec![
-                TokenKind::Integer(0),
-                TokenKind::SymbolRoundBracketLeft,
-            ]))
+    // arrays of unknown length are completed by their initializers
+    fn complete_type(ty: Type, initializer: &Initializer) -> Type {
+        match (ty, initializer) {
+            (
+                Type::Array {
+                    element,
+                    length: None,
+                },
+                Initializer::List(elements),
+            ) => Type::array_of(*element, Some(elements.len())),
+            (ty, _) => ty,
         }
     }
 
-    fn next_numeric_value(&mut self) -> Option<i32> {
-        let token = self.tokens.peek()?;
+    // compound literals at file scope are static objects and need constant initializers
+    // otherwise they are unnamed locals, including those in statements of the implicit main
+    fn consume_compound_literal(&mut self, ty: Type, index: usize) -> Result<Node> {
+        let initializer = self.consume_initializer(&ty)?;
+        let ty = Self::complete_type(ty, &initializer);
+        if !ty.is_complete() {
+            return Err(Self::incomplete_type_error(&ty, index));
+        }
+        let label = format!(".Lcompound.{}", self.get_next_label_suffix());
+        if self.is_file_scope() {
+            if let Some((data, relocations)) = Self::evaluate_initializer_data(&initializer, &ty) {
+                self.variables.push(VariableDefinition {
+                    label: label.to_owned(),
+                    ty: ty.to_owned(),
+                    is_static: true,
+                    is_thread_local: false,
+                    data: Some(data),
+                    relocations,
+                });
+                return Ok(Node::GlobalVariable {
+                    identifier: label.to_owned(),
+                    label,
+                    ty,
+                });
+            }
+        }
 
-        if let TokenKind::Integer(v) = token.kind {
-            self.tokens.next();
-            Some(v)
-        } else {
-            None
+        let object = self
+            .declare_variable(label.to_owned(), ty, index)?
+            .into_node(label);
+        let mut initialization = Vec::<Node>::new();
+        if object.ty().is_aggregate() {
+            initialization.push(Node::ZeroFill {
+                target: object.to_owned().into(),
+            });
         }
-    }
+        Self::new_initialization(object.to_owned(), initializer, &mut initialization);
 
-    fn next_identifier(&mut self) -> Option<String> {
-        let token = self.tokens.peek()?;
+        Ok(Node::CompoundLiteral {
+            object: object.into(),
+            initialization,
+        })
+    }
 
-        if let TokenKind::Identifier(ref v) = token.kind {
-            let identifier = v.to_owned();
-            self.tokens.next();
+    // string literals are arrays of char with static storage duration
+    fn new_string_literal(&mut self, mut value: Vec<u8>) -> Node {
+        value.push(0);
+        let label = format!(".Lstring.{}", self.get_next_label_suffix());
+        let ty = Type::array_of(Type::Char, Some(value.len()));
+        self.variables.push(VariableDefinition {
+            label: label.to_owned(),
+            ty: ty.to_owned(),
+            is_static: true,
+            is_thread_local: false,
+            data: Some(value),
+            relocations: Vec::new(),
+        });
 
-            Some(identifier)
-        } else {
-            None
+        Node::GlobalVariable {
+            identifier: label.to_owned(),
+            label,
+            ty,
         }
     }
 
-    fn next_keyword_return(&mut self) -> Option<()> {
-        self.tokens
-            .next_if(|token| token.kind == TokenKind::KeywordReturn)
-            .map(|_| ())
-    }
+    // the initial image of a variable with a constant initializer
+    // along with the addresses of other objects in it
+    fn evaluate_initializer_data(
+        initializer: &Initializer,
+        ty: &Type,
+    ) -> Option<(Vec<u8>, Vec<Relocation>)> {
+        let mut data = vec![0; ty.size()];
+        let mut relocations = Vec::<Relocation>::new();
+        Self::write_initializer_data(initializer, ty, 0, &mut data, &mut relocations)?;
 
-    fn next_keyword_if(&mut self) -> Option<()> {
-        self.tokens
-            .next_if(|token| token.kind == TokenKind::KeywordIf)
-            .map(|_| ())
+        Some((data, relocations))
     }
 
-    fn next_keyword_else(&mut self) -> Option<()> {
-        self.tokens
-            .next_if(|token| token.kind == TokenKind::KeywordElse)
-            .map(|_| ())
-    }
+    fn write_initializer_data(
+        initializer: &Initializer,
+        ty: &Type,
+        offset: usize,
+        data: &mut [u8],
+        relocations: &mut Vec<Relocation>,
+    ) -> Option<()> {
+        match initializer {
+            Initializer::Expression(value) => {
+                if let Some(bytes) = Self::evaluate_scalar_data(value, ty) {
+                    data[offset..offset + bytes.len()].copy_from_slice(&bytes);
+                } else if ty.size() == 8 {
+                    let (label, addend) = value.evaluate_address_constant()?;
+                    relocations.push(Relocation {
+                        offset,
+                        label,
+                        addend,
+                    });
+                } else {
+                    return None;
+                }
+            }
+            Initializer::List(elements) => {
+                for (index, element) in elements.iter().enumerate() {
+                    let Some(element) = element else {
+                        continue;
+                    };
+                    let (element_ty, element_offset) = Self::aggregate_element(ty, index);
+                    if let Some(bit_field) = Self::aggregate_bit_field(ty, index) {
+                        // bits of a bit-field are merged into its storage unit
+                        let Initializer::Expression(value) = element else {
+                            unreachable!("bit-fields are scalars")
+                        };
+                        let offset = offset + element_offset;
+                        let size = element_ty.size();
+                        let mut unit = [0; 8];
+                        unit[..size].copy_from_slice(&data[offset..offset + size]);
+                        let mask = u64::MAX >> (64 - bit_field.width);
+                        let bits =
+                            (value.evaluate_long_constant()? as u64 & mask) << bit_field.bit_offset;
+                        let unit =
+                            (u64::from_le_bytes(unit) & !(mask << bit_field.bit_offset)) | bits;
+                        data[offset..offset + size].copy_from_slice(&unit.to_le_bytes()[..size]);
+                    } else {
+                        Self::write_initializer_data(
+                            element,
+                            &element_ty,
+                            offset + element_offset,
+                            data,
+                            relocations,
+                        )?;
+                    }
+                }
+            }
+        }
 
-    fn next_keyword_while(&mut self) -> Option<()> {
-        self.tokens
-            .next_if(|token| token.kind == TokenKind::KeywordWhile)
-            .map(|_| ())
+        Some(())
     }
 
-    fn next_keyword_for(&mut self) -> Option<()> {
-        self.tokens
-            .next_if(|token| token.kind == TokenKind::KeywordFor)
-            .map(|_| ())
-    }
+    // the bytes of a constant scalar as laid out in memory
+    fn evaluate_scalar_data(value: &Node, ty: &Type) -> Option<Vec<u8>> {
+        let bytes = match ty.unqualified() {
+            Type::Float => (value.evaluate_floating_constant()? as f32)
+                .to_le_bytes()
+                .to_vec(),
+            Type::Double => value.evaluate_floating_constant()?.to_le_bytes().to_vec(),
+            Type::Struct { .. } => return None,
+            _ => value.evaluate_long_constant()?.to_le_bytes()[..ty.size()].to_vec(),
+        };
 
-    fn next_symbol_plus(&mut self) -> Option<()> {
-        self.tokens
-            .next_if(|token| token.kind == TokenKind::SymbolPlus)
-            .map(|_| ())
+        Some(bytes)
     }
 
-    fn next_symbol_minus(&mut self) -> Option<()> {
-        self.tokens
-            .next_if(|token| token.kind == TokenKind::SymbolMinus)
-            .map(|_| ())
+    // stores the elements given by an initializer to the object it initializes
+    fn new_initialization(target: Node, initializer: Initializer, statements: &mut Vec<Node>) {
+        match initializer {
+            Initializer::Expression(value) => statements.push(Self::new_assign(target, value)),
+            Initializer::List(elements) => {
+                for (index, element) in elements.into_iter().enumerate() {
+                    if let Some(element) = element {
+                        let target = Self::new_element(target.to_owned(), index);
+                        Self::new_initialization(target, element, statements);
+                    }
+                }
+            }
+        }
     }
 
-    fn next_symbol_star(&mut self) -> Option<()> {
-        self.tokens
-            .next_if(|token| token.kind == TokenKind::SymbolStar)
-            .map(|_| ())
+    // the element of an array or the member of a struct at index
+    fn new_element(target: Node, index: usize) -> Node {
+        match target.ty() {
+            Type::Array { .. } => Node::Dereference {
+                value: Self::new_add(
+                    Self::decay(target),
+                    Node::Integer {
+                        value: index as i32,
+                    },
+                )
+                .into(),
+            },
+            Type::Struct { members, .. } => Node::Member {
+                value: target.into(),
+                member: members[index].to_owned(),
+            },
+            _ => unreachable!("scalars have no elements"),
+        }
     }
 
-    fn next_symbol_slash(&mut self) -> Option<()> {
-        self.tokens
-            .next_if(|token| token.kind == TokenKind::SymbolSlash)
-            .map(|_| ())
+    fn aggregate_bit_field(ty: &Type, index: usize) -> Option<BitField> {
+        match ty.unqualified() {
+            Type::Struct { members, .. } => members[index].bit_field,
+            _ => None,
+        }
     }
 
-    fn next_symbol_round_bracket_left(&mut self) -> Option<()> {
-        self.tokens
-            .next_if(|token| token.kind == TokenKind::SymbolRoundBracketLeft)
-            .map(|_| ())
+    // type and offset of the element of an array or the member of a struct at index
+    fn aggregate_element(ty: &Type, index: usize) -> (Type, usize) {
+        match ty.unqualified() {
+            Type::Array { element, .. } => (element.as_ref().to_owned(), index * element.size()),
+            Type::Struct { members, .. } => (members[index].ty.to_owned(), members[index].offset),
+            _ => unreachable!("scalars have no elements"),
+        }
     }
 
-    fn next_symbol_round_bracket_right(&mut self) -> Option<()> {
-        self.tokens
-            .next_if(|token| token.kind == TokenKind::SymbolRoundBracketRight)
-            .map(|_| ())
+    // the number of elements an initializer list can give, unbounded for arrays of unknown length
+    fn aggregate_capacity(ty: &Type) -> Option<usize> {
+        match ty.unqualified() {
+            Type::Array { length, .. } => *length,
+            Type::Struct { members, .. } => Some(members.len()),
+            _ => unreachable!("scalars have no elements"),
+        }
     }
 
-    fn next_symbol_angle_bracket_left(&mut self) -> Option<()> {
-        self.tokens
-            .next_if(|token| token.kind == TokenKind::SymbolAngleBracketLeft)
-            .map(|_| ())
-    }
+    // consumes the initializer after an equal sign, if any, along with the index it starts at
+    fn consume_optional_initializer(&mut self, ty: &Type) -> Result<Option<(usize, Initializer)>> {
+        if self.next_symbol_equal().is_none() {
+            return Ok(None);
+        }
 
-    fn next_symbol_angle_bracket_right(&mut self) -> Option<()> {
-        self.tokens
-            .next_if(|token| token.kind == TokenKind::SymbolAngleBracketRight)
-            .map(|_| ())
+        let index = self.current_index_in_text().unwrap_or(self.text.len());
+        Ok(Some((index, self.consume_initializer(ty)?)))
     }
 
-    fn next_symbol_angle_bracket_left_and_equal(&mut self) -> Option<()> {
-        self.tokens
-            .next_if(|token| token.kind == TokenKind::SymbolAngleBracketLeftAndEqual)
-            .map(|_| ())
-    }
+    fn consume_initializer(&mut self, ty: &Type) -> Result<Initializer> {
+        if let Some(initializer) = self.consume_string_initializer(ty)? {
+            return Ok(initializer);
+        }
 
-    fn next_symbol_angle_bracket_right_and_equal(&mut self) -> Option<()> {
-        self.tokens
-            .next_if(|token| token.kind == TokenKind::SymbolAngleBracketRightAndEqual)
-            .map(|_| ())
+        if self.next_symbol_curly_bracket_left().is_some() {
+            if ty.is_aggregate() {
+                let mut elements = Vec::<Option<Initializer>>::new();
+                self.consume_initializer_list(ty, &mut elements, true, 0)?;
+                return Ok(Initializer::List(elements));
+            }
+
+            // scalars may be enclosed in braces too
+            let initializer = self.consume_initializer(ty)?;
+            self.next_symbol_comma();
+            if self.next_symbol_curly_bracket_right().is_none() {
+                return Err(self.error_unexpected_token(vec![TokenKind::SymbolCurlyBracketRight]));
+            }
+            return Ok(initializer);
+        }
+
+        // structs may be initialized by expressions of the same type
+        if matches!(ty.unqualified(), Type::Struct { .. }) {
+            let index = self.current_index_in_text().unwrap_or(self.text.len());
+            let value = self.consume_assign()?;
+            if value.ty() != *ty.unqualified() {
+                return Err(CompileError::not_a_struct(index));
+            }
+            return Ok(Initializer::Expression(value));
+        }
+        if ty.is_aggregate() {
+            return Err(self.error_unexpected_token(vec![TokenKind::SymbolCurlyBracketLeft]));
+        }
+        let index = self.current_index_in_text().unwrap_or(self.text.len());
+        let value = self.consume_assign()?;
+        Self::check_assignment(&value, ty, index)?;
+        Ok(Initializer::Expression(Self::convert(value, ty)))
     }
 
-    fn next_symbol_curly_bracket_left(&mut self) -> Option<()> {
-        self.tokens
+    // arrays of char may be initialized by string literals, optionally enclosed in braces
+    // the terminating null is left out when the array is just long enough for the rest
+    fn consume_string_initializer(&mut self, ty: &Type) -> Result<Option<Initializer>> {
+        let Type::Array { element, length } = ty else {
+            return Ok(None);
+        };
+        if !matches!(
+            element.unqualified(),
+            Type::Char | Type::SignedChar | Type::UnsignedChar
+        ) {
+            return Ok(None);
+        }
+        let mut tokens = self.tokens.clone();
+        let is_braced = tokens
             .next_if(|token| token.kind == TokenKind::SymbolCurlyBracketLeft)
-            .map(|_| ())
-    }
+            .is_some();
+        if !tokens
+            .peek()
+            .is_some_and(|token| matches!(token.kind, TokenKind::String(_)))
+        {
+            return Ok(None);
+        }
 
-    fn next_symbol_curly_bracket_right(&mut self) -> Option<()> {
-        self.tokens
-            .next_if(|token| token.kind == TokenKind::SymbolCurlyBracketRight)
-            .map(|_| ())
-    }
+        if is_braced {
+            self.next_symbol_curly_bracket_left();
+        }
+        let mut value = self.next_string_literal().unwrap();
+        value.push(0);
+        if let Some(length) = length {
+            value.truncate(*length);
+        }
+        if is_braced {
+            self.next_symbol_comma();
+            if self.next_symbol_curly_bracket_right().is_none() {
+                return Err(self.error_unexpected_token(vec![TokenKind::SymbolCurlyBracketRight]));
+            }
+        }
 
-    fn next_symbol_double_equal(&mut self) -> Option<()> {
-        self.tokens
-            .next_if(|token| token.kind == TokenKind::SymbolDoubleEqual)
-            .map(|_| ())
+        let elements = value
+            .into_iter()
+            .map(|byte| {
+                let value = Node::Integer { value: byte as i32 };
+                Some(Initializer::Expression(Self::convert(value, element)))
+            })
+            .collect();
+        Ok(Some(Initializer::List(elements)))
     }
 
-    fn next_symbol_exclamation_and_equal(&mut self) -> Option<()> {
-        self.tokens
-            .next_if(|token| token.kind == TokenKind::SymbolExclamationAndEqual)
-            .map(|_| ())
+    // fills the elements of an aggregate from an initializer list, starting at index
+    // without braces, the list belongs to an enclosing aggregate and only as many elements
+    // as this one has are taken from it, leaving the rest and any designators to the enclosing one
+    fn consume_initializer_list(
+        &mut self,
+        ty: &Type,
+        elements: &mut Vec<Option<Initializer>>,
+        is_braced: bool,
+        mut index: usize,
+    ) -> Result {
+        let capacity = Self::aggregate_capacity(ty);
+        loop {
+            if is_braced && self.next_symbol_curly_bracket_right().is_some() {
+                return Ok(());
+            }
+
+            let element_index = self.current_index_in_text().unwrap_or(self.text.len());
+            if self.is_designator_next() {
+                if !is_braced {
+                    return Ok(());
+                }
+                index = self.consume_designation(ty, elements)?;
+            } else if capacity.is_some_and(|capacity| index >= capacity) {
+                return if is_braced {
+                    Err(CompileError::excess_initializer(element_index))
+                } else {
+                    Ok(())
+                };
+            } else {
+                self.consume_initializer_element(ty, elements, index)?;
+            }
+            index += 1;
+
+            if !is_braced {
+                let is_full = capacity.is_some_and(|capacity| index >= capacity);
+                if is_full || !self.is_comma_followed_by_element() {
+                    return Ok(());
+                }
+                self.next_symbol_comma();
+            } else if self.next_symbol_comma().is_none() {
+                if self.next_symbol_curly_bracket_right().is_none() {
+                    return Err(
+                        self.error_unexpected_token(vec![TokenKind::SymbolCurlyBracketRight])
+                    );
+                }
+                return Ok(());
+            }
+        }
     }
 
-    fn next_symbol_equal(&mut self) -> Option<()> {
-        self.tokens
-            .next_if(|token| token.kind == TokenKind::SymbolEqual)
-            .map(|_| ())
+    // braces may be omitted around the initializers of nested aggregates
+    fn consume_initializer_element(
+        &mut self,
+        ty: &Type,
+        elements: &mut Vec<Option<Initializer>>,
+        index: usize,
+    ) -> Result {
+        let (element_ty, _) = Self::aggregate_element(ty, index);
+        // flexible array members cannot be initialized
+        if !element_ty.is_complete() {
+            let index = self.current_index_in_text().unwrap_or(self.text.len());
+            return Err(Self::incomplete_type_error(&element_ty, index));
+        }
+        if elements.len() <= index {
+            elements.resize_with(index + 1, || None);
+        }
+
+        let is_braced = self
+            .tokens
+            .peek()
+            .is_some_and(|token| token.kind == TokenKind::SymbolCurlyBracketLeft);
+        let initializer = match self.consume_string_initializer(&element_ty)? {
+            Some(initializer) => initializer,
+            None if element_ty.is_aggregate() && !is_braced => {
+                let mut sub_elements = match elements[index].take() {
+                    Some(Initializer::List(sub_elements)) => sub_elements,
+                    _ => Vec::new(),
+                };
+                self.consume_initializer_list(&element_ty, &mut sub_elements, false, 0)?;
+                Initializer::List(sub_elements)
+            }
+            None => self.consume_initializer(&element_ty)?,
+        };
+        elements[index] = Some(initializer);
+
+        Ok(())
     }
 
-    fn next_symbol_semicolon(&mut self) -> Option<()> {
-        self.tokens
-            .next_if(|token| token.kind == TokenKind::SymbolSemicolon)
-            .map(|_| ())
+    // consumes designators and the initializer after them, returning the index designated
+    // initializers without designators continue from the element after it
+    fn consume_designation(
+        &mut self,
+        ty: &Type,
+        elements: &mut Vec<Option<Initializer>>,
+    ) -> Result<usize> {
+        let index = self.consume_designator(ty)?;
+        if elements.len() <= index {
+            elements.resize_with(index + 1, || None);
+        }
+
+        if self.is_designator_next() {
+            let (element_ty, _) = Self::aggregate_element(ty, index);
+            if !element_ty.is_aggregate() {
+                return Err(self.error_unexpected_token(vec![TokenKind::SymbolEqual]));
+            }
+            let mut sub_elements = match elements[index].take() {
+                Some(Initializer::List(sub_elements)) => sub_elements,
+                _ => Vec::new(),
+            };
+            // the elements following a nested designation continue in the same aggregate
+            let next_index = self.consume_designation(&element_ty, &mut sub_elements)? + 1;
+            let is_full = Self::aggregate_capacity(&element_ty)
+                .is_some_and(|capacity| next_index >= capacity);
+            if !is_full && self.is_comma_followed_by_element() {
+                self.next_symbol_comma();
+                self.consume_initializer_list(&element_ty, &mut sub_elements, false, next_index)?;
+            }
+            elements[index] = Some(Initializer::List(sub_elements));
+        } else {
+            if self.next_symbol_equal().is_none() {
+                return Err(self.error_unexpected_token(vec![TokenKind::SymbolEqual]));
+            }
+            self.consume_initializer_element(ty, elements, index)?;
+        }
+
+        Ok(index)
     }
 
-    fn next_symbol_comma(&mut self) -> Option<()> {
-        self.tokens
-            .next_if(|token| token.kind == TokenKind::SymbolComma)
-            .map(|_| ())
+    fn consume_designator(&mut self, ty: &Type) -> Result<usize> {
+        let index = self.current_index_in_text().unwrap_or(self.text.len());
+        match ty.unqualified() {
+            Type::Array { length, .. } if self.next_symbol_square_bracket_left().is_some() => {
+                let element_index = self
+                    .consume_assign()?
+                    .evaluate_constant()
+                    .filter(|&value| value >= 0)
+                    .ok_or_else(|| CompileError::not_a_constant(index + 1))?
+                    as usize;
+                if self.next_symbol_square_bracket_right().is_none() {
+                    return Err(
+                        self.error_unexpected_token(vec![TokenKind::SymbolSquareBracketRight])
+                    );
+                }
+                if length.is_some_and(|length| element_index >= length) {
+                    return Err(CompileError::excess_initializer(index));
+                }
+                Ok(element_index)
+            }
+            Type::Struct { members, .. } if self.next_symbol_dot().is_some() => {
+                let name_index = self.current_index_in_text().unwrap_or(self.text.len());
+                let name = self.next_identifier().ok_or_else(|| {
+                    self.error_unexpected_token(vec![TokenKind::Identifier(String::new())])
+                })?;
+                members
+                    .iter()
+                    .position(|member| member.name == name)
+                    .ok_or_else(|| CompileError::unknown_member(name_index, name))
+            }
+            Type::Array { .. } => {
+                Err(self.error_unexpected_token(vec![TokenKind::SymbolSquareBracketLeft]))
+            }
+            _ => Err(self.error_unexpected_token(vec![TokenKind::SymbolDot])),
+        }
     }
 
-    fn next_eof(&mut self) -> Option<()> {
-        self.tokens
-            .next_if(|token| token.kind == TokenKind::EOF)
-            .map(|_| ())
+    fn is_designator_next(&mut self) -> bool {
+        self.tokens.peek().is_some_and(|token| {
+            matches!(
+                token.kind,
+                TokenKind::SymbolSquareBracketLeft | TokenKind::SymbolDot
+            )
+        })
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    // a comma is followed by another element unless it is a trailing one or a designator follows
+    fn is_comma_followed_by_element(&mut self) -> bool {
+        let mut tokens = self.tokens.clone();
+        if tokens.next().map(|token| token.kind) != Some(TokenKind::SymbolComma) {
+            return false;
+        }
 
-    #[test]
-    fn number() {
-        let mut parser = Parser::new("  1234567890 ;  ");
-        assert_eq!(
-            parser.consume_statement().unwrap(),
-            Node::Integer { value: 1234567890 }
-        );
-        assert!(parser.next_eof().is_some());
+        !tokens.peek().is_some_and(|token| {
+            matches!(
+                token.kind,
+                TokenKind::SymbolCurlyBracketRight
+                    | TokenKind::SymbolSquareBracketLeft
+                    | TokenKind::SymbolDot
+            )
+        })
+    }
+
+    // a parenthesized declarator binds looser than the suffixes following it
+    // so they are applied to the type first and the inner declarator is parsed afterwards
+    fn consume_declarator(&mut self, ty: Type) -> Result<Declarator> {
+        let ty = self.consume_pointers(ty);
+
+        if self.is_nested_declarator() {
+            self.next_symbol_round_bracket_left();
+            let inner_tokens = self.tokens.clone();
+            self.skip_parenthesized()?;
+            let (ty, _) = self.consume_type_suffix(ty)?;
+            let outer_tokens = std::mem::replace(&mut self.tokens, inner_tokens);
+            let declarator = self.consume_declarator(ty)?;
+            if self.next_symbol_round_bracket_right().is_none() {
+                return Err(self.error_unexpected_token(vec![TokenKind::SymbolRoundBracketRight]));
+            }
+            self.tokens = outer_tokens;
+
+            return Ok(declarator);
+        }
+
+        let index = self.current_index_in_text().unwrap_or(self.text.len());
+        let identifier = self.next_identifier();
+        let (ty, parameters) = self.consume_type_suffix(ty)?;
+
+        Ok(Declarator {
+            identifier,
+            index,
+            ty,
+            parameters,
+        })
+    }
+
+    // a parameter list starts with a type or is empty, unlike a nested declarator
+    fn is_nested_declarator(&mut self) -> bool {
+        let mut tokens = self.tokens.clone();
+        if tokens.next().map(|token| token.kind) != Some(TokenKind::SymbolRoundBracketLeft) {
+            return false;
+        }
+
+        tokens.peek().is_some_and(|token| {
+            matches!(
+                token.kind,
+                TokenKind::SymbolStar
+                    | TokenKind::SymbolRoundBracketLeft
+                    | TokenKind::Identifier(_)
+            )
+        })
+    }
+
+    // skips tokens up to the parenthesis closing an already consumed one
+    fn skip_parenthesized(&mut self) -> Result {
+        let mut depth = 1;
+        while depth > 0 {
+            match self.tokens.next().map(|token| token.kind) {
+                Some(TokenKind::SymbolRoundBracketLeft) => depth += 1,
+                Some(TokenKind::SymbolRoundBracketRight) => depth -= 1,
+                Some(TokenKind::Eof) | None => {
+                    return Err(CompileError::unexpected_eof(self.text.len()))
+                }
+                _ => (),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn consume_type_suffix(&mut self, ty: Type) -> Result<(Type, Vec<Declarator>)> {
+        if self.next_symbol_round_bracket_left().is_some() {
+            // empty parentheses declare a function without a prototype
+            if self.next_symbol_round_bracket_right().is_some() {
+                return Ok((Type::function_without_prototype(ty), Vec::new()));
+            }
+            let (parameters, is_variadic) = self.consume_parameters()?;
+            let parameter_types = parameters
+                .iter()
+                .map(|parameter| parameter.ty.to_owned())
+                .collect();
+            return Ok((
+                Type::function_returning(ty, parameter_types, is_variadic),
+                parameters,
+            ));
+        }
+
+        // the length of an array applies outside of the suffixes following it
+        // lengths of arrays in blocks may be computed at run time
+        if self.next_symbol_square_bracket_left().is_some() {
+            let index = self.current_index_in_text().unwrap_or(self.text.len());
+            let length = if self.next_symbol_square_bracket_right().is_some() {
+                None
+            } else {
+                let length = self.consume_assign()?;
+                if self.next_symbol_square_bracket_right().is_none() {
+                    return Err(
+                        self.error_unexpected_token(vec![TokenKind::SymbolSquareBracketRight])
+                    );
+                }
+                Some(length)
+            };
+            let (element, _) = self.consume_type_suffix(ty)?;
+            if !element.is_complete() {
+                return Err(Self::incomplete_type_error(&element, index));
+            }
+            let length = match length {
+                None => None,
+                Some(length) => match length.evaluate_constant() {
+                    Some(length) if length >= 0 && !element.is_variably_modified() => {
+                        Some(length as usize)
+                    }
+                    None if length.ty().is_integer() && !self.is_file_scope() => {
+                        return Ok((self.new_variable_array(element, length), Vec::new()))
+                    }
+                    Some(_) if element.is_variably_modified() => {
+                        return Ok((self.new_variable_array(element, length), Vec::new()))
+                    }
+                    _ => return Err(CompileError::not_a_constant(index)),
+                },
+            };
+            return Ok((Type::array_of(element, length), Vec::new()));
+        }
+
+        Ok((ty, Vec::new()))
+    }
+
+    // the size is computed into a hidden local from the length and the size of the element
+    fn new_variable_array(&mut self, element: Type, length: Node) -> Type {
+        let size = self.declare_hidden_variable("vla.size", Type::Long);
+        let Node::LocalVariable {
+            offset: size_offset,
+            ..
+        } = size
+        else {
+            unreachable!("hidden variables are locals")
+        };
+        let (lhs, rhs) =
+            Self::convert_operands(Self::convert(length, &Type::Long), Self::size_of(&element));
+        self.function
+            .variable_array_sizes
+            .push(Self::new_assign(size, Node::OperatorMul { lhs, rhs }));
+
+        Type::VariableArray {
+            element: element.into(),
+            size_offset,
+        }
+    }
+
+    // sizes of variable length arrays are loaded from where they were computed
+    fn size_of(ty: &Type) -> Node {
+        match ty.unqualified() {
+            Type::VariableArray { size_offset, .. } => Node::LocalVariable {
+                identifier: String::from(".Lvla.size"),
+                offset: *size_offset,
+                ty: Type::Long,
+            },
+            ty => Node::Integer {
+                value: ty.size() as i32,
+            },
+        }
+    }
+
+    // consumes a parameter list after its opening parenthesis
+    // and tells whether it ends with an ellipsis
+    fn consume_parameters(&mut self) -> Result<(Vec<Declarator>, bool)> {
+        let mut parameters = Vec::<Declarator>::new();
+        let mut is_variadic = false;
+        loop {
+            if self.next_symbol_ellipsis().is_some() {
+                is_variadic = true;
+                break;
+            }
+
+            let ty = self
+                .consume_type_specifier()?
+                .ok_or_else(|| self.error_unexpected_token(vec![TokenKind::KeywordInt]))?;
+            let mut parameter = self.consume_declarator(ty)?;
+            if parameter.ty.is_variably_modified() {
+                return Err(CompileError::not_a_constant(parameter.index));
+            }
+            // a sole unnamed void declares no parameters
+            if parameter.ty == Type::Void && parameter.identifier.is_none() && parameters.is_empty()
+            {
+                break;
+            }
+            // parameters of function and array types, including va_list, are adjusted to pointers
+            if parameter.ty.is_function() || parameter.ty == Type::VaList {
+                parameter.ty = Type::pointer_to(parameter.ty);
+            } else if let Type::Array { element, .. } = parameter.ty {
+                parameter.ty = Type::pointer_to(*element);
+            }
+            parameters.push(parameter);
+
+            if self.next_symbol_comma().is_none() {
+                break;
+            }
+        }
+        if self.next_symbol_round_bracket_right().is_none() {
+            return Err(self.error_unexpected_token(vec![TokenKind::SymbolRoundBracketRight]));
+        }
+
+        Ok((parameters, is_variadic))
+    }
+
+    // storage classes are only accepted in front of type specifiers
+    // _Thread_local may come along with static or extern, which tells whether it is given
+    fn consume_declaration_specifiers(
+        &mut self,
+    ) -> Result<Option<(Option<StorageClass>, bool, Type)>> {
+        let (mut storage_class, mut is_thread_local) = (None, false);
+        loop {
+            let index = self.current_index_in_text().unwrap_or(self.text.len());
+            if self.next_keyword_thread_local().is_some() {
+                if is_thread_local {
+                    return Err(CompileError::invalid_storage_class(index));
+                }
+                is_thread_local = true;
+            } else if let Some(class) = self.next_storage_class() {
+                if storage_class.is_some() {
+                    return Err(CompileError::invalid_storage_class(index));
+                }
+                storage_class = Some(class);
+            } else {
+                break;
+            }
+        }
+        match self.consume_type_specifier()? {
+            Some(ty) => Ok(Some((storage_class, is_thread_local, ty))),
+            None if storage_class.is_none() && !is_thread_local => Ok(None),
+            None => Err(self.error_unexpected_token(vec![TokenKind::KeywordInt])),
+        }
+    }
+
+    // qualifiers may come before, between and after type specifiers
+    fn consume_type_specifier(&mut self) -> Result<Option<Type>> {
+        let index = self.current_index_in_text().unwrap_or(self.text.len());
+        let (mut is_const, mut is_volatile) = self.consume_type_qualifiers();
+        let mut align = self.consume_alignment_specifiers()?;
+        let struct_ty = match self.next_keyword_struct() {
+            Some(_) => Some(self.consume_struct_specifier()?),
+            None => None,
+        };
+        let mut specifiers = Vec::<TokenKind>::new();
+        loop {
+            let (has_const, has_volatile) = self.consume_type_qualifiers();
+            (is_const, is_volatile) = (is_const || has_const, is_volatile || has_volatile);
+            align = align.max(self.consume_alignment_specifiers()?);
+            match self.next_type_specifier() {
+                Some(specifier) => specifiers.push(specifier),
+                None => break,
+            }
+        }
+
+        let ty = match struct_ty {
+            Some(ty) if specifiers.is_empty() => ty,
+            Some(_) => return Err(CompileError::invalid_type_specifier(index)),
+            None if specifiers.is_empty() && !is_const && !is_volatile && align.is_none() => {
+                return Ok(None)
+            }
+            None if specifiers.is_empty() => {
+                return Err(self.error_unexpected_token(vec![TokenKind::KeywordInt]))
+            }
+            None => Self::type_from_specifiers(&specifiers, index)?,
+        };
+        let ty = Type::qualified(ty, is_const, is_volatile);
+
+        match align {
+            // _Alignas(0) has no effect
+            Some(0) | None => Ok(Some(ty)),
+            Some(align) if align < ty.align() => Err(CompileError::invalid_alignment(index)),
+            Some(align) => Ok(Some(Type::aligned(ty, align))),
+        }
+    }
+
+    // takes the strictest of the alignments given by _Alignas(constant) or _Alignas(type name)
+    fn consume_alignment_specifiers(&mut self) -> Result<Option<usize>> {
+        let mut align = None;
+        while self.next_keyword_alignas().is_some() {
+            let index = self.current_index_in_text().unwrap_or(self.text.len());
+            let value =
+                match self.consume_cast_type()? {
+                    Some(ty) => ty.align(),
+                    None => {
+                        if self.next_symbol_round_bracket_left().is_none() {
+                            return Err(self
+                                .error_unexpected_token(vec![TokenKind::SymbolRoundBracketLeft]));
+                        }
+                        let value = self
+                            .consume_assign()?
+                            .evaluate_constant()
+                            .ok_or_else(|| CompileError::not_a_constant(index))?;
+                        if value < 0 || value != 0 && !(value as usize).is_power_of_two() {
+                            return Err(CompileError::invalid_alignment(index));
+                        }
+                        if self.next_symbol_round_bracket_right().is_none() {
+                            return Err(self
+                                .error_unexpected_token(vec![TokenKind::SymbolRoundBracketRight]));
+                        }
+                        value as usize
+                    }
+                };
+            align = align.max(Some(value));
+        }
+
+        Ok(align)
+    }
+
+    fn type_from_specifiers(specifiers: &[TokenKind], index: usize) -> Result<Type> {
+        match specifiers[..] {
+            [TokenKind::KeywordFloat] => return Ok(Type::Float),
+            [TokenKind::KeywordDouble] => return Ok(Type::Double),
+            [TokenKind::KeywordVoid] => return Ok(Type::Void),
+            [TokenKind::KeywordVaList] => return Ok(Type::VaList),
+            [TokenKind::KeywordBool] => return Ok(Type::Bool),
+            _ => (),
+        }
+
+        let count = |kind: TokenKind| specifiers.iter().filter(|&k| *k == kind).count();
+        if count(TokenKind::KeywordFloat)
+            + count(TokenKind::KeywordDouble)
+            + count(TokenKind::KeywordVoid)
+            + count(TokenKind::KeywordVaList)
+            + count(TokenKind::KeywordBool)
+            + count(TokenKind::KeywordStruct)
+            > 0
+        {
+            return Err(CompileError::invalid_type_specifier(index));
+        }
+        let (signed, unsigned) = match (
+            count(TokenKind::KeywordSigned),
+            count(TokenKind::KeywordUnsigned),
+        ) {
+            (0, 0) => (false, false),
+            (1, 0) => (true, false),
+            (0, 1) => (false, true),
+            _ => return Err(CompileError::invalid_type_specifier(index)),
+        };
+        let ty = match (
+            count(TokenKind::KeywordChar),
+            count(TokenKind::KeywordShort),
+            count(TokenKind::KeywordInt),
+            count(TokenKind::KeywordLong),
+        ) {
+            (1, 0, 0, 0) if unsigned => Type::UnsignedChar,
+            (1, 0, 0, 0) if signed => Type::SignedChar,
+            (1, 0, 0, 0) => Type::Char,
+            (0, 1, 0 | 1, 0) if unsigned => Type::UnsignedShort,
+            (0, 1, 0 | 1, 0) => Type::Short,
+            (0, 0, 0 | 1, 0) if unsigned => Type::UnsignedInt,
+            (0, 0, 0 | 1, 0) => Type::Int,
+            (0, 0, 0 | 1, 1) if unsigned => Type::UnsignedLong,
+            (0, 0, 0 | 1, 1) => Type::Long,
+            (0, 0, 0 | 1, 2) if unsigned => Type::UnsignedLongLong,
+            (0, 0, 0 | 1, 2) => Type::LongLong,
+            _ => return Err(CompileError::invalid_type_specifier(index)),
+        };
+
+        Ok(ty)
+    }
+
+    // tells whether const and volatile are among the qualifiers
+    fn consume_type_qualifiers(&mut self) -> (bool, bool) {
+        let (mut is_const, mut is_volatile) = (false, false);
+        while let Some(qualifier) = self.next_type_qualifier() {
+            match qualifier {
+                TokenKind::KeywordConst => is_const = true,
+                _ => is_volatile = true,
+            }
+        }
+
+        (is_const, is_volatile)
+    }
+
+    // consumes a struct specifier after its keyword
+    // a tag without members refers to a struct declared before or declares an incomplete one
+    // which is also visible to the members of its own definition
+    fn consume_struct_specifier(&mut self) -> Result<Type> {
+        let index = self.current_index_in_text().unwrap_or(self.text.len());
+        let tag = self.next_identifier();
+        if self.next_symbol_curly_bracket_left().is_none() {
+            let tag = tag.ok_or_else(|| {
+                self.error_unexpected_token(vec![
+                    TokenKind::Identifier(String::new()),
+                    TokenKind::SymbolCurlyBracketLeft,
+                ])
+            })?;
+            if let Some(ty) = self.get_tag_by_name(&tag) {
+                return Ok(ty);
+            }
+            let ty = Type::IncompleteStruct {
+                tag: tag.to_owned(),
+            };
+            self.current_scope_mut().tags.insert(tag, ty.to_owned());
+            return Ok(ty);
+        }
+        if let Some(tag) = &tag {
+            let scope = self.current_scope_mut();
+            if scope.tags.get(tag).is_some_and(Type::is_complete) {
+                return Err(CompileError::redefinition(index, tag.to_owned()));
+            }
+            let ty = Type::IncompleteStruct {
+                tag: tag.to_owned(),
+            };
+            scope.tags.insert(tag.to_owned(), ty);
+        }
+
+        let mut members = Vec::<(Option<String>, Type, Option<usize>)>::new();
+        let mut member_indices = Vec::<usize>::new();
+        while self.next_symbol_curly_bracket_right().is_none() {
+            let ty = self
+                .consume_type_specifier()?
+                .ok_or_else(|| self.error_unexpected_token(vec![TokenKind::KeywordInt]))?;
+            loop {
+                let declarator = self.consume_declarator(ty.to_owned())?;
+                if declarator.ty.is_variably_modified() {
+                    return Err(CompileError::not_a_constant(declarator.index));
+                }
+                let width = self.consume_bit_field_width(&declarator)?;
+                let name = match declarator.identifier {
+                    Some(name) => name,
+                    // bit-fields may be unnamed
+                    None if width.is_some() => {
+                        members.push((None, declarator.ty, width));
+                        member_indices.push(declarator.index);
+                        if self.next_symbol_comma().is_none() {
+                            break;
+                        }
+                        continue;
+                    }
+                    None => {
+                        return Err(CompileError::unexpected_token(
+                            declarator.index,
+                            vec![TokenKind::Identifier(String::new())],
+                        ))
+                    }
+                };
+                if members
+                    .iter()
+                    .any(|(member, ..)| member.as_ref() == Some(&name))
+                {
+                    return Err(CompileError::redefinition(declarator.index, name));
+                }
+                if width == Some(0) {
+                    return Err(CompileError::invalid_bit_field(declarator.index));
+                }
+                members.push((Some(name), declarator.ty, width));
+                member_indices.push(declarator.index);
+
+                if self.next_symbol_comma().is_none() {
+                    break;
+                }
+            }
+            if self.next_symbol_semicolon().is_none() {
+                return Err(self.error_unexpected_token(vec![TokenKind::SymbolSemicolon]));
+            }
+        }
+
+        // only the last of several members may be a flexible array
+        for (i, ((_, ty, _), index)) in members.iter().zip(&member_indices).enumerate() {
+            let is_flexible_array = i > 0
+                && i == members.len() - 1
+                && matches!(ty, Type::Array { length: None, element } if element.is_complete());
+            if !ty.is_complete() && !is_flexible_array {
+                return Err(Self::incomplete_type_error(ty, *index));
+            }
+        }
+
+        let ty = Type::struct_of(members);
+        if let Some(tag) = tag {
+            self.current_scope_mut().tags.insert(tag, ty.to_owned());
+        }
+
+        Ok(ty)
+    }
+
+    // bit-fields are members of integer types followed by their widths in bits
+    fn consume_bit_field_width(&mut self, declarator: &Declarator) -> Result<Option<usize>> {
+        if self.next_symbol_colon().is_none() {
+            return Ok(None);
+        }
+        let index = self.current_index_in_text().unwrap_or(self.text.len());
+        let width = self
+            .consume_assign()?
+            .evaluate_constant()
+            .ok_or_else(|| CompileError::not_a_constant(index))?;
+        if !declarator.ty.is_integer() || width < 0 || width as usize > 8 * declarator.ty.size() {
+            return Err(CompileError::invalid_bit_field(declarator.index));
+        }
+
+        Ok(Some(width as usize))
+    }
+
+    fn incomplete_type_error(ty: &Type, index: usize) -> CompileError {
+        let name = match ty.unqualified() {
+            Type::IncompleteStruct { tag } => format!("struct {}", tag),
+            Type::Void => String::from("void"),
+            Type::Array { element, .. } if element.is_complete() => {
+                String::from("array of unknown length")
+            }
+            Type::Array { element, .. } => return Self::incomplete_type_error(element, index),
+            _ => unreachable!("{:?} is complete", ty),
+        };
+        CompileError::incomplete_type(index, name)
+    }
+
+    // pointers to structs declared before their definitions are converted to pointers
+    // to the structs defined by then, which are required to be complete to be accessed
+    fn complete_pointee(&self, pointer: Node, index: usize) -> Result<Node> {
+        let Some(pointee) = pointer.ty().pointee().cloned() else {
+            return Ok(pointer);
+        };
+        let complete = match pointee {
+            Type::Qualified {
+                ty,
+                is_const,
+                is_volatile,
+                align,
+            } => Type::Qualified {
+                ty: self.complete_struct(*ty).into(),
+                is_const,
+                is_volatile,
+                align,
+            },
+            ty => self.complete_struct(ty),
+        };
+        // void is incomplete, but pointers to it can still be added to and dereferenced
+        if !complete.is_complete()
+            && !matches!(complete.unqualified(), Type::Array { .. } | Type::Void)
+        {
+            return Err(Self::incomplete_type_error(&complete, index));
+        }
+
+        Ok(Self::convert(pointer, &Type::pointer_to(complete)))
+    }
+
+    fn complete_struct(&self, ty: Type) -> Type {
+        match ty {
+            Type::IncompleteStruct { tag } => self
+                .get_tag_by_name(&tag)
+                .unwrap_or(Type::IncompleteStruct { tag }),
+            ty => ty,
+        }
+    }
+
+    // alignments given by _Alignas apply to the pointers declared rather than to their pointees
+    fn consume_pointers(&mut self, ty: Type) -> Type {
+        let (mut ty, align) = ty.split_align();
+        while self.next_symbol_star().is_some() {
+            let (is_const, is_volatile) = self.consume_type_qualifiers();
+            ty = Type::qualified(Type::pointer_to(ty), is_const, is_volatile);
+        }
+
+        match align {
+            Some(align) => Type::aligned(ty, align),
+            None => ty,
+        }
+    }
+
+    // consumes a parenthesized type name only when the parenthesis starts with a type
+    fn consume_cast_type(&mut self) -> Result<Option<Type>> {
+        if !self.is_type_name_in_brackets_next() {
+            return Ok(None);
+        }
+
+        self.next_symbol_round_bracket_left();
+        let ty = self.consume_type_name()?;
+        if self.next_symbol_round_bracket_right().is_none() {
+            return Err(self.error_unexpected_token(vec![TokenKind::SymbolRoundBracketRight]));
+        }
+
+        Ok(Some(ty))
+    }
+
+    // the type name of sizeof may have variable length arrays whose sizes it computes
+    fn consume_sizeof_type(&mut self) -> Result<Option<(Type, Vec<Node>)>> {
+        if !self.is_type_name_in_brackets_next() {
+            return Ok(None);
+        }
+
+        self.next_symbol_round_bracket_left();
+        let ty_and_sizes = self.consume_type_name_and_sizes()?;
+        if self.next_symbol_round_bracket_right().is_none() {
+            return Err(self.error_unexpected_token(vec![TokenKind::SymbolRoundBracketRight]));
+        }
+
+        Ok(Some(ty_and_sizes))
+    }
+
+    fn is_type_name_in_brackets_next(&self) -> bool {
+        let mut tokens = self.tokens.clone();
+        tokens.next().map(|token| token.kind) == Some(TokenKind::SymbolRoundBracketLeft)
+            && tokens.peek().is_some_and(|token| {
+                token.kind.is_type_specifier() || token.kind.is_type_qualifier()
+            })
+    }
+
+    fn consume_type_name(&mut self) -> Result<Type> {
+        let index = self.current_index_in_text().unwrap_or(self.text.len());
+        let (ty, _) = self.consume_type_name_and_sizes()?;
+        // only declarations and sizeof compute the sizes of variable length arrays
+        if ty.is_variably_modified() {
+            return Err(CompileError::not_a_constant(index));
+        }
+
+        Ok(ty)
+    }
+
+    // also returns the stores of the sizes of variable length arrays in the type
+    fn consume_type_name_and_sizes(&mut self) -> Result<(Type, Vec<Node>)> {
+        let ty = self
+            .consume_type_specifier()?
+            .ok_or_else(|| self.error_unexpected_token(vec![TokenKind::KeywordInt]))?;
+        let declarator = self.consume_declarator(ty)?;
+        if declarator.identifier.is_some() {
+            return Err(CompileError::unexpected_token(
+                declarator.index,
+                vec![TokenKind::SymbolRoundBracketRight],
+            ));
+        }
+
+        Ok((
+            declarator.ty,
+            std::mem::take(&mut self.function.variable_array_sizes),
+        ))
+    }
+
+    // conversions yield values, which are never qualified
+    fn convert(node: Node, ty: &Type) -> Node {
+        let ty = ty.unqualified();
+        if node.ty() == *ty {
+            node
+        } else {
+            Node::Cast {
+                value: node.into(),
+                ty: ty.to_owned(),
+            }
+        }
+    }
+
+    // integer promotion of a value
+    // as in GCC, bit-fields promote according to their widths rather than their declared types
+    // so those narrower than int promote to int even if unsigned or declared long
+    fn promoted_ty(node: &Node) -> Type {
+        let ty = node.ty().unqualified().promoted();
+        match node.bit_field() {
+            Some(BitField { width, .. }) if width < 32 => Type::Int,
+            Some(BitField { width: 32, .. }) if ty.is_signed() => Type::Int,
+            Some(BitField { width: 32, .. }) => Type::UnsignedInt,
+            _ => ty,
+        }
+    }
+
+    // applies the usual arithmetic conversions to operands of a binary operator
+    fn convert_operands(lhs: Node, rhs: Node) -> (Box<Node>, Box<Node>) {
+        if lhs.ty().is_pointer() || rhs.ty().is_pointer() {
+            return (lhs.into(), rhs.into());
+        }
+
+        let (lhs_ty, rhs_ty) = (Self::promoted_ty(&lhs), Self::promoted_ty(&rhs));
+        let ty = Type::common_type(&lhs_ty, &rhs_ty);
+        (
+            Self::convert(lhs, &ty).into(),
+            Self::convert(rhs, &ty).into(),
+        )
+    }
+
+    // operands of multiplicative operators, and of the others unless they take pointers
+    fn check_arithmetic_operands(lhs: &Node, rhs: &Node, index: usize) -> Result {
+        if !lhs.ty().is_arithmetic() || !rhs.ty().is_arithmetic() {
+            return Err(CompileError::invalid_operands(index));
+        }
+
+        Ok(())
+    }
+
+    // pointers are added to integers and subtracted from pointers to compatible types
+    fn check_additive_operands(lhs: &Node, rhs: &Node, is_sub: bool, index: usize) -> Result {
+        let (lhs_ty, rhs_ty) = (lhs.ty(), rhs.ty());
+        let is_valid = match (lhs_ty.pointee(), rhs_ty.pointee()) {
+            (Some(lhs_pointee), Some(rhs_pointee)) => {
+                is_sub
+                    && lhs_pointee
+                        .unqualified()
+                        .is_compatible(rhs_pointee.unqualified())
+            }
+            (Some(_), None) => rhs_ty.is_integer(),
+            (None, Some(_)) => !is_sub && lhs_ty.is_integer(),
+            (None, None) => return Self::check_arithmetic_operands(lhs, rhs, index),
+        };
+        if !is_valid {
+            return Err(CompileError::invalid_operands(index));
+        }
+
+        Ok(())
+    }
+
+    // pointers are compared with pointers, and for equality with null pointer constants
+    fn check_compared_operands(lhs: &Node, rhs: &Node, is_equality: bool, index: usize) -> Result {
+        let is_null = |node: &Node| {
+            is_equality && node.ty().is_integer() && node.evaluate_long_constant() == Some(0)
+        };
+        let is_valid = match (lhs.ty().is_pointer(), rhs.ty().is_pointer()) {
+            (true, true) => true,
+            (true, false) => is_null(rhs),
+            (false, true) => is_null(lhs),
+            (false, false) => return Self::check_arithmetic_operands(lhs, rhs, index),
+        };
+        if !is_valid {
+            return Err(CompileError::invalid_operands(index));
+        }
+
+        Ok(())
+    }
+
+    // conditions are scalars, floating ones being compared with zero since -0.0 has non-zero bits
+    fn new_condition(condition: Node, index: usize) -> Result<Node> {
+        let ty = condition.ty();
+        if !ty.is_scalar() {
+            return Err(CompileError::not_a_scalar(index));
+        }
+        if !ty.is_floating() {
+            return Ok(condition);
+        }
+
+        Ok(Node::OperatorNe {
+            lhs: condition.into(),
+            rhs: Self::convert(Node::Integer { value: 0 }, &ty).into(),
+        })
+    }
+
+    // function designators are converted to pointers to the functions
+    // and so are va_list objects to pointers to their only element
+    fn decay(node: Node) -> Node {
+        let ty = node.ty();
+        if ty.is_function() || ty == Type::VaList {
+            Node::Address { value: node.into() }
+        } else if let Type::Array { element, .. } | Type::VariableArray { element, .. } = ty {
+            // arrays decay to pointers to their first elements
+            Node::Cast {
+                value: Node::Address { value: node.into() }.into(),
+                ty: Type::pointer_to(*element),
+            }
+        } else {
+            node
+        }
+    }
+
+    // the type of an operand as it was before it decayed to a pointer
+    fn undecayed_ty(node: &Node) -> Type {
+        match node {
+            Node::Address { value } if value.ty().is_function() || value.ty() == Type::VaList => {
+                value.ty()
+            }
+            Node::Cast { value, .. } => match value.as_ref() {
+                Node::Address { value: array }
+                    if matches!(array.ty(), Type::Array { .. } | Type::VariableArray { .. }) =>
+                {
+                    array.ty()
+                }
+                _ => node.ty(),
+            },
+            _ => node.ty(),
+        }
+    }
+
+    // default argument promotions apply to arguments without parameters in a prototype
+    fn new_argument(argument: Node) -> Node {
+        let ty = match argument.ty() {
+            Type::Float => Type::Double,
+            _ => Self::promoted_ty(&argument),
+        };
+        Self::convert(argument, &ty)
+    }
+
+    // arithmetic values convert to each other, pointers to pointers and structs to the same ones
+    // null pointer constants convert to pointers too
+    fn is_convertible(value: &Node, ty: &Type) -> bool {
+        let (from, to) = (value.ty(), ty.unqualified());
+        if to.is_pointer() {
+            from.is_pointer() || from.is_integer() && value.evaluate_long_constant() == Some(0)
+        } else if to.is_aggregate() {
+            from == *to
+        } else if *to == Type::Bool {
+            from.is_arithmetic() || from.is_pointer()
+        } else {
+            from.is_arithmetic()
+        }
+    }
+
+    // pointer arguments must point to compatible types, unless either points to void,
+    // and parameters must keep the qualifiers of what the arguments point to
+    fn is_pointer_argument_compatible(argument: &Node, parameter: &Type) -> bool {
+        let argument_ty = argument.ty();
+        let (Some(from), Some(to)) = (argument_ty.pointee(), parameter.pointee()) else {
+            return true;
+        };
+        let (from_ty, to_ty) = (from.unqualified(), to.unqualified());
+        (*from_ty == Type::Void || *to_ty == Type::Void || from_ty.is_compatible(to_ty))
+            && (!from.is_const() || to.is_const())
+            && (!from.is_volatile() || to.is_volatile())
+    }
+
+    // values assigned, used to initialize objects or returned must not be void
+    // and must be convertible to the types of their targets
+    fn check_assignment(value: &Node, ty: &Type, index: usize) -> Result {
+        if value.ty() == Type::Void {
+            return Err(CompileError::void_value(index));
+        }
+        if !Self::is_convertible(value, ty) {
+            return Err(CompileError::incompatible_assignment(index));
+        }
+
+        Ok(())
+    }
+
+    fn new_assign(lhs: Node, rhs: Node) -> Node {
+        let rhs = Self::convert(rhs, &lhs.ty());
+        Node::OperatorAssign {
+            lhs: lhs.into(),
+            rhs: rhs.into(),
+        }
+    }
+
+    fn new_add(lhs: Node, rhs: Node) -> Node {
+        if let Some(pointee) = lhs.ty().pointee() {
+            let rhs = Self::scale_pointer_offset(rhs, pointee);
+            Node::OperatorAdd {
+                lhs: lhs.into(),
+                rhs: rhs.into(),
+            }
+        } else if let Some(pointee) = rhs.ty().pointee() {
+            let lhs = Self::scale_pointer_offset(lhs, pointee);
+            Node::OperatorAdd {
+                lhs: rhs.into(),
+                rhs: lhs.into(),
+            }
+        } else {
+            let (lhs, rhs) = Self::convert_operands(lhs, rhs);
+            Node::OperatorAdd { lhs, rhs }
+        }
+    }
+
+    fn new_sub(lhs: Node, rhs: Node) -> Node {
+        match (lhs.ty().pointee(), rhs.ty().is_pointer()) {
+            (Some(pointee), true) => {
+                let (lhs, rhs) = Self::convert_operands(
+                    Node::OperatorSub {
+                        lhs: lhs.into(),
+                        rhs: rhs.into(),
+                    },
+                    Self::size_of(pointee),
+                );
+                Node::OperatorDiv { lhs, rhs }
+            }
+            (Some(pointee), false) => {
+                let rhs = Self::scale_pointer_offset(rhs, pointee);
+                Node::OperatorSub {
+                    lhs: lhs.into(),
+                    rhs: rhs.into(),
+                }
+            }
+            (None, _) => {
+                let (lhs, rhs) = Self::convert_operands(lhs, rhs);
+                Node::OperatorSub { lhs, rhs }
+            }
+        }
+    }
+
+    fn scale_pointer_offset(offset: Node, pointee: &Type) -> Node {
+        let (lhs, rhs) =
+            Self::convert_operands(Self::convert(offset, &Type::Long), Self::size_of(pointee));
+        Node::OperatorMul { lhs, rhs }
+    }
+
+    fn consume_expression(&mut self) -> Result<Node> {
+        self.consume_assign()
+    }
+
+    fn consume_assign(&mut self) -> Result<Node> {
+        let index = self.current_index_in_text().unwrap_or(self.text.len());
+        let equality = self.consume_equality()?;
+
+        if self.next_symbol_equal().is_some() {
+            if !equality.is_left_value() {
+                return Err(CompileError::not_a_left_value(index));
+            }
+            if equality.is_read_only() {
+                return Err(CompileError::read_only_left_value(index));
+            }
+
+            let assign_index = self.current_index_in_text().unwrap_or(self.text.len());
+            let assign = self.consume_assign()?;
+            Self::check_assignment(&assign, &equality.ty(), assign_index)?;
+            Ok(Self::new_assign(equality, assign))
+        } else {
+            Ok(equality)
+        }
+    }
+
+    fn consume_equality(&mut self) -> Result<Node> {
+        let mut node = self.consume_relational()?;
+
+        loop {
+            let index = self.current_index_in_text().unwrap_or(self.text.len());
+            node = if self.next_symbol_double_equal().is_some() {
+                let rhs = self.consume_relational()?;
+                Self::check_compared_operands(&node, &rhs, true, index)?;
+                let (lhs, rhs) = Self::convert_operands(node, rhs);
+                Node::OperatorEq { lhs, rhs }
+            } else if self.next_symbol_exclamation_and_equal().is_some() {
+                let rhs = self.consume_relational()?;
+                Self::check_compared_operands(&node, &rhs, true, index)?;
+                let (lhs, rhs) = Self::convert_operands(node, rhs);
+                Node::OperatorNe { lhs, rhs }
+            } else {
+                break Ok(node);
+            }
+        }
+    }
+
+    fn consume_relational(&mut self) -> Result<Node> {
+        let mut node = self.consume_add()?;
+
+        loop {
+            let index = self.current_index_in_text().unwrap_or(self.text.len());
+            node = if self.next_symbol_angle_bracket_left().is_some() {
+                let rhs = self.consume_add()?;
+                Self::check_compared_operands(&node, &rhs, false, index)?;
+                let (lhs, rhs) = Self::convert_operands(node, rhs);
+                Node::OperatorLt { lhs, rhs }
+            } else if self.next_symbol_angle_bracket_right().is_some() {
+                let lhs = self.consume_add()?;
+                Self::check_compared_operands(&lhs, &node, false, index)?;
+                let (lhs, rhs) = Self::convert_operands(lhs, node);
+                Node::OperatorLt { lhs, rhs }
+            } else if self.next_symbol_angle_bracket_left_and_equal().is_some() {
+                let rhs = self.consume_add()?;
+                Self::check_compared_operands(&node, &rhs, false, index)?;
+                let (lhs, rhs) = Self::convert_operands(node, rhs);
+                Node::OperatorLtEq { lhs, rhs }
+            } else if self.next_symbol_angle_bracket_right_and_equal().is_some() {
+                let lhs = self.consume_add()?;
+                Self::check_compared_operands(&lhs, &node, false, index)?;
+                let (lhs, rhs) = Self::convert_operands(lhs, node);
+                Node::OperatorLtEq { lhs, rhs }
+            } else {
+                break Ok(node);
+            }
+        }
+    }
+
+    fn consume_add(&mut self) -> Result<Node> {
+        let mut node = self.consume_mul()?;
+
+        loop {
+            let index = self.current_index_in_text().unwrap_or(self.text.len());
+            node = if self.next_symbol_plus().is_some() {
+                let rhs = self.consume_mul()?;
+                let (lhs, rhs) = (
+                    self.complete_pointee(node, index)?,
+                    self.complete_pointee(rhs, index)?,
+                );
+                Self::check_additive_operands(&lhs, &rhs, false, index)?;
+                Self::new_add(lhs, rhs)
+            } else if self.next_symbol_minus().is_some() {
+                let rhs = self.consume_mul()?;
+                let (lhs, rhs) = (
+                    self.complete_pointee(node, index)?,
+                    self.complete_pointee(rhs, index)?,
+                );
+                Self::check_additive_operands(&lhs, &rhs, true, index)?;
+                Self::new_sub(lhs, rhs)
+            } else {
+                break Ok(node);
+            }
+        }
+    }
+
+    fn consume_mul(&mut self) -> Result<Node> {
+        let mut node = self.consume_cast()?;
+
+        loop {
+            let index = self.current_index_in_text().unwrap_or(self.text.len());
+            node = if self.next_symbol_star().is_some() {
+                let rhs = self.consume_cast()?;
+                Self::check_arithmetic_operands(&node, &rhs, index)?;
+                let (lhs, rhs) = Self::convert_operands(node, rhs);
+                Node::OperatorMul { lhs, rhs }
+            } else if self.next_symbol_slash().is_some() {
+                let rhs = self.consume_cast()?;
+                Self::check_arithmetic_operands(&node, &rhs, index)?;
+                let (lhs, rhs) = Self::convert_operands(node, rhs);
+                Node::OperatorDiv { lhs, rhs }
+            } else {
+                break Ok(node);
+            }
+        }
+    }
+
+    fn consume_cast(&mut self) -> Result<Node> {
+        let index = self.current_index_in_text().unwrap_or(self.text.len());
+        if let Some(ty) = self.consume_cast_type()? {
+            // a braced initializer after a parenthesized type name makes a compound literal
+            if self
+                .tokens
+                .peek()
+                .is_some_and(|token| token.kind == TokenKind::SymbolCurlyBracketLeft)
+            {
+                let node = self.consume_compound_literal(ty, index)?;
+                return self.consume_postfix_operators(Self::decay(node), index);
+            }
+            let value_index = self.current_index_in_text().unwrap_or(self.text.len());
+            let value = self.consume_cast()?;
+            // anything can be discarded by casting it to void
+            // while other casts take scalars to scalars, except between pointers and floating values
+            if *ty.unqualified() != Type::Void {
+                if !ty.is_scalar() {
+                    return Err(CompileError::not_a_scalar(index));
+                }
+                let value_ty = value.ty();
+                if !value_ty.is_scalar() {
+                    return Err(CompileError::not_a_scalar(value_index));
+                }
+                if ty.is_pointer() && value_ty.is_floating()
+                    || ty.is_floating() && value_ty.is_pointer()
+                {
+                    return Err(CompileError::invalid_cast(index));
+                }
+            }
+            return Ok(Node::Cast {
+                value: value.into(),
+                ty: ty.unqualified().to_owned(),
+            });
+        }
+
+        self.consume_unary()
+    }
+
+    fn consume_unary(&mut self) -> Result<Node> {
+        let index = self.current_index_in_text().unwrap_or(self.text.len());
+
+        if self.next_symbol_plus().is_some() {
+            let value = self.consume_cast()?;
+            if !value.ty().is_arithmetic() {
+                return Err(CompileError::invalid_operands(index));
+            }
+            let ty = Self::promoted_ty(&value);
+            return Ok(Self::convert(value, &ty));
+        } else if self.next_symbol_minus().is_some() {
+            let value = self.consume_cast()?;
+            if !value.ty().is_arithmetic() {
+                return Err(CompileError::invalid_operands(index));
+            }
+            let (lhs, rhs) = Self::convert_operands(Node::Integer { value: 0 }, value);
+            return Ok(Node::OperatorSub { lhs, rhs });
+        } else if self.next_symbol_star().is_some() {
+            let value = self.consume_cast()?;
+            if !value.ty().is_pointer() {
+                return Err(CompileError::not_a_pointer(index));
+            }
+            let value = self.complete_pointee(value, index)?;
+            return Ok(Self::decay(Node::Dereference {
+                value: value.into(),
+            }));
+        } else if self.next_keyword_sizeof().is_some() {
+            // operands are not evaluated, as only their types are needed
+            let (ty, sizes) = match self.consume_sizeof_type()? {
+                Some(ty_and_sizes) => ty_and_sizes,
+                None => (Self::undecayed_ty(&self.consume_unary()?), Vec::new()),
+            };
+            if !ty.is_complete() {
+                return Err(Self::incomplete_type_error(&ty, index));
+            }
+            let size = Self::convert(Self::size_of(&ty), &Type::UnsignedLong);
+            if sizes.is_empty() {
+                return Ok(size);
+            }
+            return Ok(Node::VariableArraySizes {
+                sizes,
+                value: size.into(),
+            });
+        } else if self.next_keyword_alignof().is_some() {
+            let ty = self.consume_cast_type()?.ok_or_else(|| {
+                self.error_unexpected_token(vec![TokenKind::SymbolRoundBracketLeft])
+            })?;
+            if !ty.is_complete() {
+                return Err(Self::incomplete_type_error(&ty, index));
+            }
+            return Ok(Self::convert(
+                Node::Integer {
+                    value: ty.align() as i32,
+                },
+                &Type::UnsignedLong,
+            ));
+        } else if self.next_symbol_ampersand().is_some() {
+            let value = self.consume_cast()?;
+            // function designators have already decayed to their addresses
+            if matches!(&value, Node::Address { value: function } if function.ty().is_function()) {
+                return Ok(value);
+            }
+            // and arrays to the addresses of their first elements
+            if let Node::Cast { value: address, .. } = &value {
+                if matches!(address.as_ref(), Node::Address { value: array } if matches!(array.ty(), Type::Array { .. } | Type::VariableArray { .. }))
+                {
+                    return Ok(address.as_ref().to_owned());
+                }
+            }
+            if !value.is_left_value() {
+                return Err(CompileError::not_a_left_value(index));
+            }
+            if value.bit_field().is_some() {
+                return Err(CompileError::invalid_bit_field(index));
+            }
+            return Ok(Node::Address {
+                value: value.into(),
+            });
+        }
+
+        self.consume_postfix()
+    }
+
+    fn consume_postfix(&mut self) -> Result<Node> {
+        let index = self.current_index_in_text().unwrap_or(self.text.len());
+        let node = self.consume_primary()?;
+        self.consume_postfix_operators(node, index)
+    }
+
+    fn consume_postfix_operators(&mut self, mut node: Node, index: usize) -> Result<Node> {
+        loop {
+            let operator_index = self.current_index_in_text().unwrap_or(self.text.len());
+            node = if self.next_symbol_round_bracket_left().is_some() {
+                self.consume_function_call(node, index)?
+            } else if self.next_symbol_square_bracket_left().is_some() {
+                let offset = self.consume_expression()?;
+                if self.next_symbol_square_bracket_right().is_none() {
+                    return Err(
+                        self.error_unexpected_token(vec![TokenKind::SymbolSquareBracketRight])
+                    );
+                }
+                let (node, offset) = (
+                    self.complete_pointee(node, operator_index)?,
+                    self.complete_pointee(offset, operator_index)?,
+                );
+                if !node.ty().is_pointer() && !offset.ty().is_pointer() {
+                    return Err(CompileError::not_a_pointer(operator_index));
+                }
+                Self::check_additive_operands(&node, &offset, false, operator_index)?;
+                let value = Self::new_add(node, offset);
+                Self::decay(Node::Dereference {
+                    value: value.into(),
+                })
+            } else if self.next_symbol_dot().is_some() {
+                self.consume_member(node, operator_index)?
+            } else if self.next_symbol_arrow().is_some() {
+                if !node.ty().is_pointer() {
+                    return Err(CompileError::not_a_pointer(operator_index));
+                }
+                let node = self.complete_pointee(node, operator_index)?;
+                let value = Node::Dereference { value: node.into() };
+                self.consume_member(value, operator_index)?
+            } else {
+                break Ok(node);
+            }
+        }
+    }
+
+    // consumes the name of a member after a dot or an arrow
+    fn consume_member(&mut self, value: Node, index: usize) -> Result<Node> {
+        let name_index = self.current_index_in_text().unwrap_or(self.text.len());
+        let name = self.next_identifier().ok_or_else(|| {
+            self.error_unexpected_token(vec![TokenKind::Identifier(String::new())])
+        })?;
+        let ty = value.ty();
+        if let Type::IncompleteStruct { .. } = ty {
+            return Err(Self::incomplete_type_error(&ty, index));
+        }
+        let Type::Struct { members, .. } = ty else {
+            return Err(CompileError::not_a_struct(index));
+        };
+        let member = members
+            .into_iter()
+            .find(|member| member.name == name)
+            .ok_or_else(|| CompileError::unknown_member(name_index, name))?;
+
+        Ok(Self::decay(Node::Member {
+            value: value.into(),
+            member,
+        }))
+    }
+
+    // consumes arguments after the opening parenthesis
+    // callees are pointers to functions since function designators decay to them
+    fn consume_function_call(&mut self, callee: Node, index: usize) -> Result<Node> {
+        let Some(Type::Function {
+            return_ty,
+            parameters,
+            is_variadic,
+            has_prototype,
+        }) = callee.ty().pointee().cloned()
+        else {
+            return Err(CompileError::not_a_function(index));
+        };
+        let ty = *return_ty;
+
+        let mut arguments = Vec::<Node>::new();
+        if self.next_symbol_round_bracket_right().is_none() {
+            loop {
+                let argument_index = self.current_index_in_text().unwrap_or(self.text.len());
+                let argument = self.consume_assign()?;
+                // arguments are converted to the types of their parameters as if by assignment
+                let argument = match parameters.get(arguments.len()) {
+                    Some(parameter)
+                        if Self::is_convertible(&argument, parameter)
+                            && Self::is_pointer_argument_compatible(&argument, parameter) =>
+                    {
+                        Self::convert(argument, parameter)
+                    }
+                    Some(_) => {
+                        return Err(CompileError::incompatible_argument(
+                            argument_index,
+                            arguments.len() + 1,
+                        ))
+                    }
+                    None if argument.ty() == Type::Void => {
+                        return Err(CompileError::void_value(argument_index))
+                    }
+                    None => Self::new_argument(argument),
+                };
+                arguments.push(argument);
+                if self.next_symbol_comma().is_none() {
+                    break;
+                }
+            }
+            if self.next_symbol_round_bracket_right().is_none() {
+                return Err(self.error_unexpected_token(vec![TokenKind::SymbolRoundBracketRight]));
+            }
+        }
+
+        let count = arguments.len();
+        let is_count_valid = match (has_prototype, is_variadic) {
+            (false, _) => true,
+            (true, false) => count == parameters.len(),
+            (true, true) => count >= parameters.len(),
+        };
+        if !is_count_valid {
+            return Err(CompileError::argument_count(index, parameters.len(), count));
+        }
+
+        // structs are returned to a temporary in the frame of the caller
+        let return_buffer = if ty.is_aggregate() {
+            let label = format!(".Lreturn.{}", self.get_next_label_suffix());
+            let variable = self.declare_variable(label.to_owned(), ty.to_owned(), index)?;
+            Some(variable.into_node(label).into())
+        } else {
+            None
+        };
+
+        Ok(Node::FunctionCall {
+            callee: callee.into(),
+            arguments,
+            ty,
+            return_buffer,
+        })
+    }
+
+    // consumes a va_list argument of the builtins, which has decayed to a pointer
+    fn consume_va_list(&mut self) -> Result<Node> {
+        let index = self.current_index_in_text().unwrap_or(self.text.len());
+        let ap = self.consume_assign()?;
+        if ap.ty() != Type::pointer_to(Type::VaList) {
+            return Err(CompileError::not_a_va_list(index));
+        }
+
+        Ok(ap)
+    }
+
+    // the va_list starts after the named parameters passed in registers
+    fn consume_va_start(&mut self, index: usize) -> Result<Node> {
+        if self.next_symbol_round_bracket_left().is_none() {
+            return Err(self.error_unexpected_token(vec![TokenKind::SymbolRoundBracketLeft]));
+        }
+        let ap = self.consume_va_list()?;
+        if self.next_symbol_comma().is_none() {
+            return Err(self.error_unexpected_token(vec![TokenKind::SymbolComma]));
+        }
+        // the last named parameter is only needed by the C macro
+        self.consume_assign()?;
+        if self.next_symbol_round_bracket_right().is_none() {
+            return Err(self.error_unexpected_token(vec![TokenKind::SymbolRoundBracketRight]));
+        }
+        if !self.function.is_variadic {
+            return Err(CompileError::not_variadic(index));
+        }
+
+        let parameter_types = &self.function.parameter_types;
+        let registers = argument_registers(parameter_types.iter());
+        let (mut integer_count, mut floating_count, mut stack_count) = (0, 0, 0);
+        for (ty, registers) in parameter_types.iter().zip(registers) {
+            match registers {
+                Some(registers) => {
+                    for register in registers {
+                        if register.starts_with("xmm") {
+                            floating_count += 1;
+                        } else {
+                            integer_count += 1;
+                        }
+                    }
+                }
+                None => stack_count += ty.size().div_ceil(8).max(1),
+            }
+        }
+        Ok(Node::VaStart {
+            ap: ap.into(),
+            gp_offset: 8 * integer_count,
+            fp_offset: 48 + 16 * floating_count,
+            overflow_offset: 8 * stack_count,
+        })
+    }
+
+    fn consume_va_arg(&mut self) -> Result<Node> {
+        if self.next_symbol_round_bracket_left().is_none() {
+            return Err(self.error_unexpected_token(vec![TokenKind::SymbolRoundBracketLeft]));
+        }
+        let ap = self.consume_va_list()?;
+        if self.next_symbol_comma().is_none() {
+            return Err(self.error_unexpected_token(vec![TokenKind::SymbolComma]));
+        }
+        let ty_index = self.current_index_in_text().unwrap_or(self.text.len());
+        let ty = self.consume_type_name()?;
+        if self.next_symbol_round_bracket_right().is_none() {
+            return Err(self.error_unexpected_token(vec![TokenKind::SymbolRoundBracketRight]));
+        }
+        // structs, which may be passed in memory or in registers, are not taken out of lists
+        if !ty.is_scalar() {
+            return Err(CompileError::not_a_scalar(ty_index));
+        }
+
+        Ok(Node::VaArg {
+            ap: ap.into(),
+            ty: ty.unqualified().to_owned(),
+        })
+    }
+
+    // selects the expression associated with the type of the controlling expression
+    // which is not evaluated and has gone through lvalue conversion, losing its qualifiers
+    // as well as decaying if it is an array or function
+    fn consume_generic_selection(&mut self, index: usize) -> Result<Node> {
+        if self.next_symbol_round_bracket_left().is_none() {
+            return Err(self.error_unexpected_token(vec![TokenKind::SymbolRoundBracketLeft]));
+        }
+        let ty = self.consume_assign()?.ty();
+        let mut associations = Vec::<(Type, Node)>::new();
+        let mut default = None;
+        while self.next_symbol_comma().is_some() {
+            let association_index = self.current_index_in_text().unwrap_or(self.text.len());
+            let association_ty = if self.next_keyword_default().is_some() {
+                if default.is_some() {
+                    return Err(CompileError::duplicate_default(association_index));
+                }
+                None
+            } else {
+                let association_ty = self.consume_type_name()?;
+                if !association_ty.is_complete() {
+                    return Err(Self::incomplete_type_error(
+                        &association_ty,
+                        association_index,
+                    ));
+                }
+                if associations.iter().any(|(ty, _)| *ty == association_ty) {
+                    return Err(CompileError::duplicate_association(association_index));
+                }
+                Some(association_ty)
+            };
+            if self.next_symbol_colon().is_none() {
+                return Err(self.error_unexpected_token(vec![TokenKind::SymbolColon]));
+            }
+            let expression = self.consume_assign()?;
+            match association_ty {
+                Some(association_ty) => associations.push((association_ty, expression)),
+                None => default = Some(expression),
+            }
+        }
+        if self.next_symbol_round_bracket_right().is_none() {
+            return Err(self.error_unexpected_token(vec![
+                TokenKind::SymbolComma,
+                TokenKind::SymbolRoundBracketRight,
+            ]));
+        }
+
+        associations
+            .into_iter()
+            .find_map(|(association_ty, expression)| (association_ty == ty).then_some(expression))
+            .or(default)
+            .ok_or_else(|| CompileError::no_matching_association(index))
+    }
+
+    // the space lasts until the function returns
+    // or a block around it in which variable length arrays are declared exits
+    fn consume_alloca(&mut self) -> Result<Node> {
+        if self.next_symbol_round_bracket_left().is_none() {
+            return Err(self.error_unexpected_token(vec![TokenKind::SymbolRoundBracketLeft]));
+        }
+        let size = Self::convert(self.consume_assign()?, &Type::UnsignedLong);
+        if self.next_symbol_round_bracket_right().is_none() {
+            return Err(self.error_unexpected_token(vec![TokenKind::SymbolRoundBracketRight]));
+        }
+
+        Ok(Node::Alloca { size: size.into() })
+    }
+
+    // nothing has to be released, so va_end only evaluates its argument
+    fn consume_va_end(&mut self) -> Result<Node> {
+        if self.next_symbol_round_bracket_left().is_none() {
+            return Err(self.error_unexpected_token(vec![TokenKind::SymbolRoundBracketLeft]));
+        }
+        let ap = self.consume_va_list()?;
+        if self.next_symbol_round_bracket_right().is_none() {
+            return Err(self.error_unexpected_token(vec![TokenKind::SymbolRoundBracketRight]));
+        }
+
+        Ok(Node::Cast {
+            value: ap.into(),
+            ty: Type::Void,
+        })
+    }
+
+    fn consume_primary(&mut self) -> Result<Node> {
+        let index = self.current_index_in_text().unwrap_or(self.text.len());
+
+        if let Some(value) = self.next_numeric_value() {
+            Ok(match i32::try_from(value) {
+                Ok(value) => Node::Integer { value },
+                Err(_) => Node::Long { value },
+            })
+        } else if self.next_integer_too_large().is_some() {
+            Err(CompileError::integer_too_large(index))
+        } else if let Some((value, ty)) = self.next_floating_value() {
+            Ok(Node::Floating { value, ty })
+        } else if let Some(value) = self.next_string_literal() {
+            Ok(Self::decay(self.new_string_literal(value)))
+        } else if let Some(identifier) = self.next_identifier() {
+            // there is no preprocessor to include stdarg.h, so its macros are built in
+            match identifier.as_str() {
+                "va_start" => return self.consume_va_start(index),
+                "va_arg" => return self.consume_va_arg(),
+                "va_end" => return self.consume_va_end(),
+                "__builtin_alloca" => return self.consume_alloca(),
+                _ => (),
+            }
+
+            if let Some(variable) = self.get_variable_by_name(&identifier) {
+                return Ok(Self::decay(variable.into_node(identifier)));
+            }
+
+            let is_called = self
+                .tokens
+                .peek()
+                .is_some_and(|token| token.kind == TokenKind::SymbolRoundBracketLeft);
+            let ty = match self.functions.get(&identifier) {
+                Some(ty) => ty.to_owned(),
+                // undeclared functions are implicitly declared to return int
+                None if is_called => {
+                    self.warnings
+                        .push(CompileWarning::implicit_function_declaration(
+                            index,
+                            identifier.to_owned(),
+                        ));
+                    let ty = Type::function_without_prototype(Type::Int);
+                    self.functions.insert(identifier.to_owned(), ty.to_owned());
+                    ty
+                }
+                None => {
+                    let variable = self.insert_implicit_variable(identifier.to_owned());
+                    return Ok(variable.into_node(identifier));
+                }
+            };
+            Ok(Self::decay(Node::Function { identifier, ty }))
+        } else if self.next_keyword_generic().is_some() {
+            self.consume_generic_selection(index)
+        } else if self.next_symbol_round_bracket_left().is_some() {
+            let node = self.consume_expression()?;
+            self.next_symbol_round_bracket_right().ok_or_else(|| {
+                self.error_unexpected_token(vec![TokenKind::SymbolRoundBracketRight])
+            })?;
+            Ok(node)
+        } else {
+            Err(self.error_unexpected_token(vec![
+                TokenKind::Integer(0),
+                TokenKind::SymbolRoundBracketLeft,
+            ]))
+        }
+    }
+
+    fn next_numeric_value(&mut self) -> Option<i64> {
+        let token = self.tokens.peek()?;
+
+        if let TokenKind::Integer(v) = token.kind {
+            self.tokens.next();
+            Some(v)
+        } else {
+            None
+        }
+    }
+
+    fn next_floating_value(&mut self) -> Option<(f64, Type)> {
+        let token = self.tokens.peek()?;
+
+        let value = match token.kind {
+            TokenKind::Float(v) => (v as f64, Type::Float),
+            TokenKind::Double(v) => (v, Type::Double),
+            _ => return None,
+        };
+        self.tokens.next();
+        Some(value)
+    }
+
+    // adjacent string literals are concatenated
+    fn next_string_literal(&mut self) -> Option<Vec<u8>> {
+        let mut value = None::<Vec<u8>>;
+        while let Some(token) = self
+            .tokens
+            .next_if(|token| matches!(token.kind, TokenKind::String(_)))
+        {
+            if let TokenKind::String(bytes) = token.kind {
+                value.get_or_insert_with(Vec::new).extend(bytes);
+            }
+        }
+
+        value
+    }
+
+    fn next_identifier(&mut self) -> Option<String> {
+        let token = self.tokens.peek()?;
+
+        if let TokenKind::Identifier(ref v) = token.kind {
+            let identifier = v.to_owned();
+            self.tokens.next();
+
+            Some(identifier)
+        } else {
+            None
+        }
+    }
+
+    fn next_label(&mut self) -> Option<String> {
+        let mut tokens = self.tokens.clone();
+        if tokens.nth(1)?.kind != TokenKind::SymbolColon {
+            return None;
+        }
+
+        let identifier = self.next_identifier()?;
+        self.next_symbol_colon();
+        Some(identifier)
+    }
+
+    fn next_keyword_return(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::KeywordReturn)
+            .map(|_| ())
+    }
+
+    fn next_keyword_if(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::KeywordIf)
+            .map(|_| ())
+    }
+
+    fn next_keyword_else(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::KeywordElse)
+            .map(|_| ())
+    }
+
+    fn next_keyword_while(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::KeywordWhile)
+            .map(|_| ())
+    }
+
+    fn next_keyword_for(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::KeywordFor)
+            .map(|_| ())
+    }
+
+    fn next_keyword_switch(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::KeywordSwitch)
+            .map(|_| ())
+    }
+
+    fn next_keyword_case(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::KeywordCase)
+            .map(|_| ())
+    }
+
+    fn next_keyword_default(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::KeywordDefault)
+            .map(|_| ())
+    }
+
+    fn next_keyword_break(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::KeywordBreak)
+            .map(|_| ())
+    }
+
+    fn next_keyword_alignas(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::KeywordAlignas)
+            .map(|_| ())
+    }
+
+    fn next_integer_too_large(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::IntegerTooLarge)
+            .map(|_| ())
+    }
+
+    fn next_keyword_generic(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::KeywordGeneric)
+            .map(|_| ())
+    }
+
+    fn next_keyword_sizeof(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::KeywordSizeof)
+            .map(|_| ())
+    }
+
+    fn next_keyword_alignof(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::KeywordAlignof)
+            .map(|_| ())
+    }
+
+    fn next_keyword_struct(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::KeywordStruct)
+            .map(|_| ())
+    }
+
+    fn next_type_specifier(&mut self) -> Option<TokenKind> {
+        self.tokens
+            .next_if(|token| token.kind.is_type_specifier())
+            .map(|token| token.kind)
+    }
+
+    fn next_type_qualifier(&mut self) -> Option<TokenKind> {
+        self.tokens
+            .next_if(|token| token.kind.is_type_qualifier())
+            .map(|token| token.kind)
+    }
+
+    fn next_storage_class(&mut self) -> Option<StorageClass> {
+        let storage_class = match self.tokens.peek()?.kind {
+            TokenKind::KeywordStatic => StorageClass::Static,
+            TokenKind::KeywordExtern => StorageClass::Extern,
+            _ => return None,
+        };
+        self.tokens.next();
+        Some(storage_class)
+    }
+
+    fn next_keyword_thread_local(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::KeywordThreadLocal)
+            .map(|_| ())
+    }
+
+    fn next_keyword_goto(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::KeywordGoto)
+            .map(|_| ())
+    }
+
+    fn next_symbol_plus(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::SymbolPlus)
+            .map(|_| ())
+    }
+
+    fn next_symbol_minus(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::SymbolMinus)
+            .map(|_| ())
+    }
+
+    fn next_symbol_star(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::SymbolStar)
+            .map(|_| ())
+    }
+
+    fn next_symbol_slash(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::SymbolSlash)
+            .map(|_| ())
+    }
+
+    fn next_symbol_ampersand(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::SymbolAmpersand)
+            .map(|_| ())
+    }
+
+    fn next_symbol_round_bracket_left(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::SymbolRoundBracketLeft)
+            .map(|_| ())
+    }
+
+    fn next_symbol_round_bracket_right(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::SymbolRoundBracketRight)
+            .map(|_| ())
+    }
+
+    fn next_symbol_angle_bracket_left(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::SymbolAngleBracketLeft)
+            .map(|_| ())
+    }
+
+    fn next_symbol_angle_bracket_right(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::SymbolAngleBracketRight)
+            .map(|_| ())
+    }
+
+    fn next_symbol_angle_bracket_left_and_equal(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::SymbolAngleBracketLeftAndEqual)
+            .map(|_| ())
+    }
+
+    fn next_symbol_angle_bracket_right_and_equal(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::SymbolAngleBracketRightAndEqual)
+            .map(|_| ())
+    }
+
+    fn next_symbol_curly_bracket_left(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::SymbolCurlyBracketLeft)
+            .map(|_| ())
+    }
+
+    fn next_symbol_curly_bracket_right(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::SymbolCurlyBracketRight)
+            .map(|_| ())
+    }
+
+    fn next_symbol_square_bracket_left(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::SymbolSquareBracketLeft)
+            .map(|_| ())
+    }
+
+    fn next_symbol_square_bracket_right(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::SymbolSquareBracketRight)
+            .map(|_| ())
+    }
+
+    fn next_symbol_dot(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::SymbolDot)
+            .map(|_| ())
+    }
+
+    fn next_symbol_arrow(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::SymbolArrow)
+            .map(|_| ())
+    }
+
+    fn next_symbol_double_equal(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::SymbolDoubleEqual)
+            .map(|_| ())
+    }
+
+    fn next_symbol_exclamation_and_equal(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::SymbolExclamationAndEqual)
+            .map(|_| ())
+    }
+
+    fn next_symbol_equal(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::SymbolEqual)
+            .map(|_| ())
+    }
+
+    fn next_symbol_colon(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::SymbolColon)
+            .map(|_| ())
+    }
+
+    fn next_symbol_semicolon(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::SymbolSemicolon)
+            .map(|_| ())
+    }
+
+    fn next_symbol_comma(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::SymbolComma)
+            .map(|_| ())
+    }
+
+    fn next_symbol_ellipsis(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::SymbolEllipsis)
+            .map(|_| ())
+    }
+
+    fn next_eof(&mut self) -> Option<()> {
+        self.tokens
+            .next_if(|token| token.kind == TokenKind::Eof)
+            .map(|_| ())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn number() {
+        let mut parser = Parser::new("  1234567890 ;  ");
+        assert_eq!(
+            parser.consume_statement().unwrap(),
+            Node::Integer { value: 1234567890 }
+        );
+        assert!(parser.next_eof().is_some());
+    }
+
+    #[test]
+    fn add() {
+        let mut parser = Parser::new("  1 + 2 ;  ");
+        assert_eq!(
+            parser.consume_statement().unwrap(),
+            Node::OperatorAdd {
+                lhs: Box::new(Node::Integer { value: 1 }),
+                rhs: Box::new(Node::Integer { value: 2 }),
+            }
+        );
+        assert!(parser.next_eof().is_some());
+    }
+
+    #[test]
+    fn sub() {
+        let mut parser = Parser::new("  1 - 2 ;  ");
+        assert_eq!(
+            parser.consume_statement().unwrap(),
+            Node::OperatorSub {
+                lhs: Box::new(Node::Integer { value: 1 }),
+                rhs: Box::new(Node::Integer { value: 2 }),
+            }
+        );
+        assert!(parser.next_eof().is_some());
+    }
+
+    #[test]
+    fn mul() {
+        let mut parser = Parser::new("  1 * 2 ;  ");
+        assert_eq!(
+            parser.consume_statement().unwrap(),
+            Node::OperatorMul {
+                lhs: Box::new(Node::Integer { value: 1 }),
+                rhs: Box::new(Node::Integer { value: 2 }),
+            }
+        );
+        assert!(parser.next_eof().is_some());
+    }
+
+    #[test]
+    fn div() {
+        let mut parser = Parser::new("  1 / 2 ;  ");
+        assert_eq!(
+            parser.consume_statement().unwrap(),
+            Node::OperatorDiv {
+                lhs: Box::new(Node::Integer { value: 1 }),
+                rhs: Box::new(Node::Integer { value: 2 }),
+            }
+        );
+        assert!(parser.next_eof().is_some());
+    }
+
+    #[test]
+    fn mul_and_add() {
+        let mut parser = Parser::new("  1 * 2 + 3 / 4 ;  ");
+        assert_eq!(
+            parser.consume_statement().unwrap(),
+            Node::OperatorAdd {
+                lhs: Box::new(Node::OperatorMul {
+                    lhs: Box::new(Node::Integer { value: 1 }),
+                    rhs: Box::new(Node::Integer { value: 2 }),
+                }),
+                rhs: Box::new(Node::OperatorDiv {
+                    lhs: Box::new(Node::Integer { value: 3 }),
+                    rhs: Box::new(Node::Integer { value: 4 }),
+                }),
+            }
+        );
+        assert!(parser.next_eof().is_some());
+    }
+
+    #[test]
+    fn nested_expression() {
+        let mut parser = Parser::new("  (1 + 2 * 3) / (4 - 5) + 6 ;  ");
+        assert_eq!(
+            parser.consume_statement().unwrap(),
+            Node::OperatorAdd {
+                lhs: Box::new(Node::OperatorDiv {
+                    lhs: Box::new(Node::OperatorAdd {
+                        lhs: Box::new(Node::Integer { value: 1 }),
+                        rhs: Box::new(Node::OperatorMul {
+                            lhs: Box::new(Node::Integer { value: 2 }),
+                            rhs: Box::new(Node::Integer { value: 3 }),
+                        }),
+                    }),
+                    rhs: Box::new(Node::OperatorSub {
+                        lhs: Box::new(Node::Integer { value: 4 }),
+                        rhs: Box::new(Node::Integer { value: 5 }),
+                    })
+                }),
+                rhs: Box::new(Node::Integer { value: 6 })
+            }
+        );
+        assert!(parser.next_eof().is_some());
+    }
+
+    #[test]
+    fn unary_plus() {
+        let mut parser = Parser::new("  1 * + 2 ;  ");
+        assert_eq!(
+            parser.consume_statement().unwrap(),
+            Node::OperatorMul {
+                lhs: Box::new(Node::Integer { value: 1 }),
+                rhs: Box::new(Node::Integer { value: 2 }),
+            }
+        );
+        assert!(parser.next_eof().is_some());
+    }
+
+    #[test]
+    fn unary_minus() {
+        let mut parser = Parser::new("  1 * - 2 ;  ");
+        assert_eq!(
+            parser.consume_statement().unwrap(),
+            Node::OperatorMul {
+                lhs: Box::new(Node::Integer { value: 1 }),
+                rhs: Box::new(Node::OperatorSub {
+                    lhs: Box::new(Node::Integer { value: 0 }),
+                    rhs: Box::new(Node::Integer { value: 2 }),
+                }),
+            }
+        );
+        assert!(parser.next_eof().is_some());
+    }
+
+    #[test]
+    fn lt_gt() {
+        let mut parser = Parser::new("  1 < 2 > 3 ;  ");
+        assert_eq!(
+            parser.consume_statement().unwrap(),
+            Node::OperatorLt {
+                lhs: Box::new(Node::Integer { value: 3 }),
+                rhs: Box::new(Node::OperatorLt {
+                    lhs: Box::new(Node::Integer { value: 1 }),
+                    rhs: Box::new(Node::Integer { value: 2 }),
+                }),
+            }
+        )
+    }
+
+    #[test]
+    fn lteq_gteq() {
+        let mut parser = Parser::new("  1 <= 2 >= 3 ;  ");
+        assert_eq!(
+            parser.consume_statement().unwrap(),
+            Node::OperatorLtEq {
+                lhs: Box::new(Node::Integer { value: 3 }),
+                rhs: Box::new(Node::OperatorLtEq {
+                    lhs: Box::new(Node::Integer { value: 1 }),
+                    rhs: Box::new(Node::Integer { value: 2 }),
+                }),
+            }
+        )
+    }
+
+    #[test]
+    fn eq_ne() {
+        let mut parser = Parser::new("  1 == 2 != 3 ;  ");
+        assert_eq!(
+            parser.consume_statement().unwrap(),
+            Node::OperatorNe {
+                lhs: Box::new(Node::OperatorEq {
+                    lhs: Box::new(Node::Integer { value: 1 }),
+                    rhs: Box::new(Node::Integer { value: 2 }),
+                }),
+                rhs: Box::new(Node::Integer { value: 3 })
+            }
+        )
+    }
+
+    #[test]
+    fn assignment() {
+        let mut parser = Parser::new("  foo = bar + 2 ;  ");
+        assert_eq!(
+            parser.consume_statement().unwrap(),
+            Node::OperatorAssign {
+                lhs: Box::new(Node::LocalVariable {
+                    identifier: String::from("foo"),
+                    offset: 8,
+                    ty: Type::Int,
+                }),
+                rhs: Box::new(Node::OperatorAdd {
+                    lhs: Box::new(Node::LocalVariable {
+                        identifier: String::from("bar"),
+                        offset: 16,
+                        ty: Type::Int,
+                    }),
+                    rhs: Box::new(Node::Integer { value: 2 }),
+                }),
+            }
+        )
+    }
+
+    #[test]
+    fn multiple_statements() {
+        let mut parser = Parser::new("  1 + 2; a;  ");
+        assert_eq!(
+            parser.parse().unwrap().functions,
+            vec![FunctionDefinition {
+                name: String::from("main"),
+                parameters: vec![],
+                is_variadic: false,
+                is_static: false,
+                stack_size: 16,
+                body: Node::Block {
+                    statements: vec![
+                        Node::OperatorAdd {
+                            lhs: Box::new(Node::Integer { value: 1 }),
+                            rhs: Box::new(Node::Integer { value: 2 })
+                        },
+                        Node::LocalVariable {
+                            identifier: String::from("a"),
+                            offset: 8,
+                            ty: Type::Int,
+                        }
+                    ]
+                },
+            }]
+        )
+    }
+
+    #[test]
+    fn return_statement() {
+        let mut parser = Parser::new("  return 1 + 2;  ");
+        assert_eq!(
+            parser.consume_statement().unwrap(),
+            Node::Return {
+                value: Some(Box::new(Node::OperatorAdd {
+                    lhs: Box::new(Node::Integer { value: 1 }),
+                    rhs: Box::new(Node::Integer { value: 2 })
+                })),
+            }
+        )
+    }
+
+    #[test]
+    fn if_statement() {
+        let mut parser = Parser::new("  if ( 1 < 2 ) 1;  ");
+        assert_eq!(
+            parser.consume_statement().unwrap(),
+            Node::If {
+                condition: Box::new(Node::OperatorLt {
+                    lhs: Box::new(Node::Integer { value: 1 }),
+                    rhs: Box::new(Node::Integer { value: 2 })
+                }),
+                statement: Box::new(Node::Integer { value: 1 }),
+                end_label: String::from(".Lend1"),
+            }
+        )
     }
 
     #[test]
-    fn add() {
-        let mut parser = Parser::new("  1 + 2 ;  ");
+    fn if_else_statement() {
+        let mut parser = Parser::new("  if ( 1 < 2 ) 1; else 2;  ");
         assert_eq!(
             parser.consume_statement().unwrap(),
-            Node::OperatorAdd {
-                lhs: Box::new(Node::Integer { value: 1 }),
-                rhs: Box::new(Node::Integer { value: 2 }),
+            Node::IfElse {
+                condition: Box::new(Node::OperatorLt {
+                    lhs: Box::new(Node::Integer { value: 1 }),
+                    rhs: Box::new(Node::Integer { value: 2 })
+                }),
+                statement: Box::new(Node::Integer { value: 1 }),
+                end_label: String::from(".Lend1"),
+                else_statement: Box::new(Node::Integer { value: 2 }),
+                else_label: String::from(".Lelse1"),
+            }
+        )
+    }
+
+    #[test]
+    fn while_statement() {
+        let mut parser = Parser::new("  while ( 1 < 2 ) 1;  ");
+        assert_eq!(
+            parser.consume_statement().unwrap(),
+            Node::While {
+                condition: Box::new(Node::OperatorLt {
+                    lhs: Box::new(Node::Integer { value: 1 }),
+                    rhs: Box::new(Node::Integer { value: 2 })
+                }),
+                statement: Box::new(Node::Integer { value: 1 }),
+                begin_label: String::from(".Lbegin1"),
+                end_label: String::from(".Lend1"),
+            }
+        )
+    }
+
+    #[test]
+    fn for_statement() {
+        let mut parser = Parser::new("  for ( i = 0; i < 10; i = i + 1 ) i;  ");
+        assert_eq!(
+            parser.consume_statement().unwrap(),
+            Node::For {
+                initializer: Some(Box::new(Node::OperatorAssign {
+                    lhs: Box::new(Node::LocalVariable {
+                        identifier: String::from("i"),
+                        offset: 8,
+                        ty: Type::Int,
+                    }),
+                    rhs: Box::new(Node::Integer { value: 0 }),
+                })),
+                condition: Some(Box::new(Node::OperatorLt {
+                    lhs: Box::new(Node::LocalVariable {
+                        identifier: String::from("i"),
+                        offset: 8,
+                        ty: Type::Int,
+                    }),
+                    rhs: Box::new(Node::Integer { value: 10 }),
+                })),
+                updater: Some(Box::new(Node::OperatorAssign {
+                    lhs: Box::new(Node::LocalVariable {
+                        identifier: String::from("i"),
+                        offset: 8,
+                        ty: Type::Int,
+                    }),
+                    rhs: Box::new(Node::OperatorAdd {
+                        lhs: Box::new(Node::LocalVariable {
+                            identifier: String::from("i"),
+                            offset: 8,
+                            ty: Type::Int,
+                        }),
+                        rhs: Box::new(Node::Integer { value: 1 })
+                    })
+                })),
+                statement: Box::new(Node::LocalVariable {
+                    identifier: String::from("i"),
+                    offset: 8,
+                    ty: Type::Int,
+                }),
+                begin_label: String::from(".Lbegin1"),
+                end_label: String::from(".Lend1")
+            }
+        )
+    }
+
+    #[test]
+    fn for_statement_abbr() {
+        let mut parser = Parser::new("  for(;;) 1;  ");
+        assert_eq!(
+            parser.consume_statement().unwrap(),
+            Node::For {
+                initializer: None,
+                condition: None,
+                updater: None,
+                statement: Box::new(Node::Integer { value: 1 }),
+                begin_label: String::from(".Lbegin1"),
+                end_label: String::from(".Lend1")
+            }
+        )
+    }
+
+    #[test]
+    fn block() {
+        let mut parser = Parser::new("  { 1; }  ");
+        assert_eq!(
+            parser.consume_statement().unwrap(),
+            Node::Block {
+                statements: vec![Node::Integer { value: 1 }],
+            }
+        )
+    }
+
+    #[test]
+    fn if_statement_with_block() {
+        let mut parser = Parser::new("  if(a < 0) { a = 0; a = 1; }  ");
+        assert_eq!(
+            parser.consume_statement().unwrap(),
+            Node::If {
+                condition: Box::new(Node::OperatorLt {
+                    lhs: Box::new(Node::LocalVariable {
+                        identifier: String::from("a"),
+                        offset: 8,
+                        ty: Type::Int,
+                    }),
+                    rhs: Box::new(Node::Integer { value: 0 })
+                }),
+                statement: Box::new(Node::Block {
+                    statements: vec![
+                        Node::OperatorAssign {
+                            lhs: Box::new(Node::LocalVariable {
+                                identifier: String::from("a"),
+                                offset: 8,
+                                ty: Type::Int,
+                            }),
+                            rhs: Box::new(Node::Integer { value: 0 })
+                        },
+                        Node::OperatorAssign {
+                            lhs: Box::new(Node::LocalVariable {
+                                identifier: String::from("a"),
+                                offset: 8,
+                                ty: Type::Int,
+                            }),
+                            rhs: Box::new(Node::Integer { value: 1 })
+                        }
+                    ]
+                }),
+                end_label: String::from(".Lend1"),
+            }
+        )
+    }
+
+    #[test]
+    fn function_call() {
+        let mut parser = Parser::new("  func();  ");
+        assert_eq!(
+            parser.consume_statement().unwrap(),
+            Node::FunctionCall {
+                callee: Box::new(Node::Address {
+                    value: Box::new(Node::Function {
+                        identifier: String::from("func"),
+                        ty: Type::function_without_prototype(Type::Int),
+                    }),
+                }),
+                arguments: vec![],
+                ty: Type::Int,
+                return_buffer: None,
+            }
+        )
+    }
+
+    #[test]
+    fn function_call_with_arguments() {
+        let mut parser = Parser::new("  func(1, 2);  ");
+        assert_eq!(
+            parser.consume_statement().unwrap(),
+            Node::FunctionCall {
+                callee: Box::new(Node::Address {
+                    value: Box::new(Node::Function {
+                        identifier: String::from("func"),
+                        ty: Type::function_without_prototype(Type::Int),
+                    }),
+                }),
+                arguments: vec![Node::Integer { value: 1 }, Node::Integer { value: 2 },],
+                ty: Type::Int,
+                return_buffer: None,
+            }
+        )
+    }
+
+    #[test]
+    fn switch_statement() {
+        let mut parser = Parser::new("  switch ( 1 ) { case 1: 2; default: break; }  ");
+        assert_eq!(
+            parser.consume_statement().unwrap(),
+            Node::Switch {
+                condition: Box::new(Node::Integer { value: 1 }),
+                statement: Box::new(Node::Block {
+                    statements: vec![
+                        Node::Label {
+                            label: String::from(".Lcase1_0"),
+                            statement: Box::new(Node::Integer { value: 2 }),
+                        },
+                        Node::Label {
+                            label: String::from(".Ldefault1"),
+                            statement: Box::new(Node::Break {
+                                end_label: String::from(".Lend1")
+                            }),
+                        },
+                    ]
+                }),
+                cases: vec![SwitchCase {
+                    value: 1,
+                    label: String::from(".Lcase1_0")
+                }],
+                default_label: Some(String::from(".Ldefault1")),
+                end_label: String::from(".Lend1"),
+            }
+        )
+    }
+
+    #[test]
+    fn switch_statement_with_duplicate_case() {
+        let mut parser = Parser::new("  switch ( 1 ) { case 1: 2; case 2 - 1: 3; }  ");
+        assert!(parser.consume_statement().is_err());
+    }
+
+    #[test]
+    fn switch_statement_on_promoted_type() {
+        let mut parser =
+            Parser::new("  unsigned u; char c; switch ( u ) case 0 - 1: switch ( c ) case 1: 2;  ");
+        let function = &parser.parse().unwrap().functions[0];
+        let Node::Block { statements } = &function.body else {
+            panic!("function bodies are blocks");
+        };
+        let Node::Switch {
+            condition,
+            statement,
+            cases,
+            ..
+        } = &statements[2]
+        else {
+            panic!("switch statements are parsed into switches");
+        };
+        assert_eq!(condition.ty(), Type::UnsignedInt);
+        assert_eq!(cases[0].value, u32::MAX as i64);
+        let Node::Label { statement, .. } = statement.as_ref() else {
+            panic!("cases are labels");
+        };
+        let Node::Switch { condition, .. } = statement.as_ref() else {
+            panic!("switch statements are parsed into switches");
+        };
+        assert_eq!(condition.ty(), Type::Int);
+
+        for text in [
+            "  double d; switch ( d ) case 1: 2;  ",
+            "  int *p; switch ( p ) case 1: 2;  ",
+            "  struct S { int a; } s; switch ( s ) case 1: 2;  ",
+        ] {
+            let mut parser = Parser::new(text);
+            assert!(parser.parse().is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn break_statement() {
+        let mut parser = Parser::new("  while ( 1 ) break;  ");
+        assert_eq!(
+            parser.consume_statement().unwrap(),
+            Node::While {
+                condition: Box::new(Node::Integer { value: 1 }),
+                statement: Box::new(Node::Break {
+                    end_label: String::from(".Lend1")
+                }),
+                begin_label: String::from(".Lbegin1"),
+                end_label: String::from(".Lend1"),
+            }
+        )
+    }
+
+    #[test]
+    fn goto_statement() {
+        let mut parser = Parser::new("  goto end; 1; end: 2;  ");
+        assert_eq!(
+            parser.parse().unwrap().functions,
+            vec![FunctionDefinition {
+                name: String::from("main"),
+                parameters: vec![],
+                is_variadic: false,
+                is_static: false,
+                stack_size: 0,
+                body: Node::Block {
+                    statements: vec![
+                        Node::Goto {
+                            label: String::from(".Luser.main.end")
+                        },
+                        Node::Integer { value: 1 },
+                        Node::Label {
+                            label: String::from(".Luser.main.end"),
+                            statement: Box::new(Node::Integer { value: 2 }),
+                        },
+                    ]
+                },
+            }]
+        )
+    }
+
+    #[test]
+    fn goto_undefined_label() {
+        let mut parser = Parser::new("  goto end; 1;  ");
+        assert!(parser.parse().is_err());
+    }
+
+    #[test]
+    fn duplicate_label() {
+        let mut parser = Parser::new("  end: 1; end: 2;  ");
+        assert!(parser.parse().is_err());
+    }
+
+    #[test]
+    fn declaration_shadowing() {
+        let mut parser = Parser::new("  int a = 1; { int a = 2; a; } a;  ");
+        let program = parser.parse().unwrap();
+        assert_eq!(
+            program.functions,
+            vec![FunctionDefinition {
+                name: String::from("main"),
+                parameters: vec![],
+                is_variadic: false,
+                is_static: false,
+                stack_size: 16,
+                body: Node::Block {
+                    statements: vec![
+                        Node::Block { statements: vec![] },
+                        Node::Block {
+                            statements: vec![
+                                Node::Block {
+                                    statements: vec![Node::OperatorAssign {
+                                        lhs: Box::new(Node::LocalVariable {
+                                            identifier: String::from("a"),
+                                            offset: 8,
+                                            ty: Type::Int,
+                                        }),
+                                        rhs: Box::new(Node::Integer { value: 2 })
+                                    }]
+                                },
+                                Node::LocalVariable {
+                                    identifier: String::from("a"),
+                                    offset: 8,
+                                    ty: Type::Int,
+                                }
+                            ]
+                        },
+                        Node::GlobalVariable {
+                            identifier: String::from("a"),
+                            label: String::from("a"),
+                            ty: Type::Int,
+                        }
+                    ]
+                },
+            }]
+        );
+        assert_eq!(
+            program.variables,
+            vec![VariableDefinition {
+                label: String::from("a"),
+                ty: Type::Int,
+                is_static: false,
+                is_thread_local: false,
+                data: Some(vec![1, 0, 0, 0]),
+                relocations: vec![],
+            }]
+        )
+    }
+
+    #[test]
+    fn sibling_blocks_share_slots() {
+        let mut parser = Parser::new("  { int a; a; } { int b; b; }  ");
+        assert_eq!(
+            parser.parse().unwrap().functions,
+            vec![FunctionDefinition {
+                name: String::from("main"),
+                parameters: vec![],
+                is_variadic: false,
+                is_static: false,
+                stack_size: 16,
+                body: Node::Block {
+                    statements: vec![
+                        Node::Block {
+                            statements: vec![
+                                Node::Block { statements: vec![] },
+                                Node::LocalVariable {
+                                    identifier: String::from("a"),
+                                    offset: 8,
+                                    ty: Type::Int,
+                                }
+                            ]
+                        },
+                        Node::Block {
+                            statements: vec![
+                                Node::Block { statements: vec![] },
+                                Node::LocalVariable {
+                                    identifier: String::from("b"),
+                                    offset: 8,
+                                    ty: Type::Int,
+                                }
+                            ]
+                        }
+                    ]
+                },
+            }]
+        )
+    }
+
+    #[test]
+    fn redefinition_in_same_scope() {
+        let mut parser = Parser::new("  int a; int a;  ");
+        assert!(parser.parse().is_err());
+    }
+
+    #[test]
+    fn cast() {
+        let mut parser = Parser::new("  (char)(1 + 2) * 3;  ");
+        assert_eq!(
+            parser.consume_statement().unwrap(),
+            Node::OperatorMul {
+                lhs: Box::new(Node::Cast {
+                    value: Box::new(Node::Cast {
+                        value: Box::new(Node::OperatorAdd {
+                            lhs: Box::new(Node::Integer { value: 1 }),
+                            rhs: Box::new(Node::Integer { value: 2 }),
+                        }),
+                        ty: Type::Char,
+                    }),
+                    ty: Type::Int,
+                }),
+                rhs: Box::new(Node::Integer { value: 3 }),
+            }
+        )
+    }
+
+    #[test]
+    fn cast_to_pointer() {
+        let mut parser = Parser::new("  (unsigned long *)(long)&a;  ");
+        assert_eq!(
+            parser.consume_statement().unwrap(),
+            Node::Cast {
+                value: Box::new(Node::Cast {
+                    value: Box::new(Node::Address {
+                        value: Box::new(Node::LocalVariable {
+                            identifier: String::from("a"),
+                            offset: 8,
+                            ty: Type::Int,
+                        })
+                    }),
+                    ty: Type::Long,
+                }),
+                ty: Type::pointer_to(Type::UnsignedLong),
             }
+        )
+    }
+
+    #[test]
+    fn invalid_casts() {
+        let mut parser = Parser::new(
+            "  struct S { int a; } s; int *p; (void)s; (long)p; (char *)1; (_Bool)p; (float)1;  ",
         );
-        assert!(parser.next_eof().is_some());
+        parser.parse().unwrap();
+
+        for text in [
+            "  struct S { int a; } s; (int)s;  ",
+            "  struct S { int a; } s; (struct S)s;  ",
+            "  (int *)1.5;  ",
+            "  int *p; (double)p;  ",
+            "  int a[2]; (int[2])a;  ",
+            "  void f(); (int)f();  ",
+        ] {
+            let mut parser = Parser::new(text);
+            assert!(parser.parse().is_err(), "{}", text);
+        }
     }
 
     #[test]
-    fn sub() {
-        let mut parser = Parser::new("  1 - 2 ;  ");
+    fn pointer_arithmetic() {
+        let mut parser = Parser::new("  long *p; *(p + 2);  ");
+        parser.consume_statement().unwrap();
+        let pointer = Node::GlobalVariable {
+            identifier: String::from("p"),
+            label: String::from("p"),
+            ty: Type::pointer_to(Type::Long),
+        };
         assert_eq!(
             parser.consume_statement().unwrap(),
-            Node::OperatorSub {
-                lhs: Box::new(Node::Integer { value: 1 }),
-                rhs: Box::new(Node::Integer { value: 2 }),
+            Node::Dereference {
+                value: Box::new(Node::OperatorAdd {
+                    lhs: Box::new(pointer),
+                    rhs: Box::new(Node::OperatorMul {
+                        lhs: Box::new(Node::Cast {
+                            value: Box::new(Node::Integer { value: 2 }),
+                            ty: Type::Long,
+                        }),
+                        rhs: Box::new(Node::Cast {
+                            value: Box::new(Node::Integer { value: 8 }),
+                            ty: Type::Long,
+                        }),
+                    }),
+                }),
             }
+        )
+    }
+
+    #[test]
+    fn invalid_operands() {
+        let mut parser = Parser::new(
+            "  int *p; char *q; int a[2]; p - a; p == 0; p < a; p[1] + 1[p]; q + 1 * 2;  ",
         );
-        assert!(parser.next_eof().is_some());
+        parser.parse().unwrap();
+
+        for text in [
+            "  int *p; p + p;  ",
+            "  int *p; char *q; p - q;  ",
+            "  int *p; 1 - p;  ",
+            "  int *p; p + 1.5;  ",
+            "  int *p; p * 2;  ",
+            "  int *p; -p;  ",
+            "  int *p; p == 1;  ",
+            "  int *p; p < 0;  ",
+            "  int *p; p[p];  ",
+            "  struct S { int a; } s; s + 1;  ",
+            "  struct S { int a; } s; s == s;  ",
+            "  struct S { int a; } s; +s;  ",
+        ] {
+            let mut parser = Parser::new(text);
+            assert!(parser.parse().is_err(), "{}", text);
+        }
     }
 
     #[test]
-    fn mul() {
-        let mut parser = Parser::new("  1 * 2 ;  ");
+    fn invalid_type_specifier() {
+        let mut parser = Parser::new("  unsigned signed char c;  ");
+        assert!(parser.consume_statement().is_err());
+    }
+
+    #[test]
+    fn usual_arithmetic_conversions() {
+        let mut parser = Parser::new("  char c; unsigned int u; long l; c + l < u;  ");
+        parser.consume_statement().unwrap();
+        parser.consume_statement().unwrap();
+        parser.consume_statement().unwrap();
         assert_eq!(
             parser.consume_statement().unwrap(),
-            Node::OperatorMul {
-                lhs: Box::new(Node::Integer { value: 1 }),
-                rhs: Box::new(Node::Integer { value: 2 }),
+            Node::OperatorLt {
+                lhs: Box::new(Node::OperatorAdd {
+                    lhs: Box::new(Node::Cast {
+                        value: Box::new(Node::GlobalVariable {
+                            identifier: String::from("c"),
+                            label: String::from("c"),
+                            ty: Type::Char,
+                        }),
+                        ty: Type::Long,
+                    }),
+                    rhs: Box::new(Node::GlobalVariable {
+                        identifier: String::from("l"),
+                        label: String::from("l"),
+                        ty: Type::Long,
+                    }),
+                }),
+                rhs: Box::new(Node::Cast {
+                    value: Box::new(Node::GlobalVariable {
+                        identifier: String::from("u"),
+                        label: String::from("u"),
+                        ty: Type::UnsignedInt,
+                    }),
+                    ty: Type::Long,
+                }),
             }
-        );
-        assert!(parser.next_eof().is_some());
+        )
     }
 
     #[test]
-    fn div() {
-        let mut parser = Parser::new("  1 / 2 ;  ");
+    fn assignment_conversion() {
+        let mut parser = Parser::new("  char c; c = 1;  ");
+        parser.consume_statement().unwrap();
         assert_eq!(
             parser.consume_statement().unwrap(),
-            Node::OperatorDiv {
-                lhs: Box::new(Node::Integer { value: 1 }),
-                rhs: Box::new(Node::Integer { value: 2 }),
+            Node::OperatorAssign {
+                lhs: Box::new(Node::GlobalVariable {
+                    identifier: String::from("c"),
+                    label: String::from("c"),
+                    ty: Type::Char,
+                }),
+                rhs: Box::new(Node::Cast {
+                    value: Box::new(Node::Integer { value: 1 }),
+                    ty: Type::Char,
+                }),
             }
-        );
-        assert!(parser.next_eof().is_some());
+        )
     }
 
     #[test]
-    fn mul_and_add() {
-        let mut parser = Parser::new("  1 * 2 + 3 / 4 ;  ");
+    fn floating_arithmetic() {
+        let mut parser = Parser::new("  1 + 2.5f * 1e1;  ");
         assert_eq!(
             parser.consume_statement().unwrap(),
             Node::OperatorAdd {
-                lhs: Box::new(Node::OperatorMul {
-                    lhs: Box::new(Node::Integer { value: 1 }),
-                    rhs: Box::new(Node::Integer { value: 2 }),
+                lhs: Box::new(Node::Cast {
+                    value: Box::new(Node::Integer { value: 1 }),
+                    ty: Type::Double,
                 }),
-                rhs: Box::new(Node::OperatorDiv {
-                    lhs: Box::new(Node::Integer { value: 3 }),
-                    rhs: Box::new(Node::Integer { value: 4 }),
+                rhs: Box::new(Node::OperatorMul {
+                    lhs: Box::new(Node::Cast {
+                        value: Box::new(Node::Floating {
+                            value: 2.5,
+                            ty: Type::Float,
+                        }),
+                        ty: Type::Double,
+                    }),
+                    rhs: Box::new(Node::Floating {
+                        value: 10.0,
+                        ty: Type::Double,
+                    }),
                 }),
             }
-        );
-        assert!(parser.next_eof().is_some());
+        )
     }
 
     #[test]
-    fn nested_expression() {
-        let mut parser = Parser::new("  (1 + 2 * 3) / (4 - 5) + 6 ;  ");
+    fn function_declaration() {
+        let mut parser = Parser::new("  double sqrt(double x); sqrt(2.0);  ");
+        parser.consume_statement().unwrap();
         assert_eq!(
             parser.consume_statement().unwrap(),
-            Node::OperatorAdd {
-                lhs: Box::new(Node::OperatorDiv {
-                    lhs: Box::new(Node::OperatorAdd {
-                        lhs: Box::new(Node::Integer { value: 1 }),
-                        rhs: Box::new(Node::OperatorMul {
-                            lhs: Box::new(Node::Integer { value: 2 }),
-                            rhs: Box::new(Node::Integer { value: 3 }),
-                        }),
+            Node::FunctionCall {
+                callee: Box::new(Node::Address {
+                    value: Box::new(Node::Function {
+                        identifier: String::from("sqrt"),
+                        ty: Type::function_returning(Type::Double, vec![Type::Double], false),
                     }),
-                    rhs: Box::new(Node::OperatorSub {
-                        lhs: Box::new(Node::Integer { value: 4 }),
-                        rhs: Box::new(Node::Integer { value: 5 }),
-                    })
                 }),
-                rhs: Box::new(Node::Integer { value: 6 })
+                arguments: vec![Node::Floating {
+                    value: 2.0,
+                    ty: Type::Double,
+                }],
+                ty: Type::Double,
+                return_buffer: None,
             }
-        );
-        assert!(parser.next_eof().is_some());
+        )
     }
 
     #[test]
-    fn unary_plus() {
-        let mut parser = Parser::new("  1 * + 2 ;  ");
+    fn function_definition() {
+        let mut parser = Parser::new("  int id(int a) { return a; } id(1);  ");
+        let id_ty = Type::function_returning(Type::Int, vec![Type::Int], false);
+        assert_eq!(
+            parser.parse().unwrap().functions,
+            vec![
+                FunctionDefinition {
+                    name: String::from("main"),
+                    parameters: vec![],
+                    is_variadic: false,
+                    is_static: false,
+                    stack_size: 0,
+                    body: Node::Block {
+                        statements: vec![Node::FunctionCall {
+                            callee: Box::new(Node::Address {
+                                value: Box::new(Node::Function {
+                                    identifier: String::from("id"),
+                                    ty: id_ty.to_owned(),
+                                }),
+                            }),
+                            arguments: vec![Node::Integer { value: 1 }],
+                            ty: Type::Int,
+                            return_buffer: None,
+                        }]
+                    },
+                },
+                FunctionDefinition {
+                    name: String::from("id"),
+                    parameters: vec![Node::LocalVariable {
+                        identifier: String::from("a"),
+                        offset: 8,
+                        ty: Type::Int,
+                    }],
+                    is_variadic: false,
+                    is_static: false,
+                    stack_size: 16,
+                    body: Node::Block {
+                        statements: vec![Node::Return {
+                            value: Some(Box::new(Node::LocalVariable {
+                                identifier: String::from("a"),
+                                offset: 8,
+                                ty: Type::Int,
+                            })),
+                        }]
+                    },
+                },
+            ]
+        )
+    }
+
+    #[test]
+    fn main_with_top_level_statements() {
+        let mut parser = Parser::new("  1; int main() { return 0; }  ");
+        assert!(parser.parse().is_err());
+    }
+
+    #[test]
+    fn function_pointer_call() {
+        let mut parser = Parser::new("  int (*fp)(int); (*fp)(1);  ");
+        parser.consume_statement().unwrap();
+        let fp = Node::GlobalVariable {
+            identifier: String::from("fp"),
+            label: String::from("fp"),
+            ty: Type::pointer_to(Type::function_returning(Type::Int, vec![Type::Int], false)),
+        };
         assert_eq!(
             parser.consume_statement().unwrap(),
-            Node::OperatorMul {
-                lhs: Box::new(Node::Integer { value: 1 }),
-                rhs: Box::new(Node::Integer { value: 2 }),
+            Node::FunctionCall {
+                callee: Box::new(Node::Address {
+                    value: Box::new(Node::Dereference {
+                        value: Box::new(fp)
+                    }),
+                }),
+                arguments: vec![Node::Integer { value: 1 }],
+                ty: Type::Int,
+                return_buffer: None,
             }
+        )
+    }
+
+    #[test]
+    fn call_of_non_function() {
+        let mut parser = Parser::new("  int a; a(1);  ");
+        parser.consume_statement().unwrap();
+        assert!(parser.consume_statement().is_err());
+    }
+
+    #[test]
+    fn variadic_function_declaration() {
+        let mut parser = Parser::new("  int printf(char *format, ...); printf;  ");
+        parser.consume_statement().unwrap();
+        assert_eq!(
+            parser.consume_statement().unwrap().ty(),
+            Type::pointer_to(Type::function_returning(
+                Type::Int,
+                vec![Type::pointer_to(Type::Char)],
+                true
+            ))
+        )
+    }
+
+    #[test]
+    fn va_start_in_non_variadic_function() {
+        let mut parser = Parser::new("  int f(int n) { va_list ap; va_start(ap, n); }  ");
+        assert!(parser.parse().is_err());
+    }
+
+    #[test]
+    fn incompatible_assignments_and_conditions() {
+        let mut parser = Parser::new(
+            "  int *p = 0; _Bool b = p; double d = 1; if (p) d = 2; while (d) d = 0; for (; p;) p = 0;  ",
         );
-        assert!(parser.next_eof().is_some());
+        parser.parse().unwrap();
+
+        for text in [
+            "  int *p; double d = p;  ",
+            "  double d; int *p = d;  ",
+            "  int *p; float f; f = p;  ",
+            "  double f(int *p) { return p; }  ",
+            "  struct S { int a; } s; int i = s;  ",
+            "  struct S { int a; } s; if (s) 1;  ",
+            "  struct S { int a; } s; while (s) 1;  ",
+            "  struct S { int a; } s; for (; s;) 1;  ",
+            "  void f(); if (f()) 1;  ",
+        ] {
+            let mut parser = Parser::new(text);
+            assert!(parser.parse().is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn void_objects() {
+        for text in [
+            "  void v; 0;  ",
+            "  int f() { void v; v = 3; return 0; }  ",
+            "  void a[3];  ",
+            "  extern void v; v;  ",
+            "  struct S { void v; };  ",
+        ] {
+            let mut parser = Parser::new(text);
+            let error = parser.parse().unwrap_err().to_string();
+            assert!(error.contains("incomplete type 'void'"), "{}", text);
+        }
+    }
+
+    #[test]
+    fn variables_and_functions_of_the_same_name() {
+        for text in [
+            "  int f; int f() { return 0; } 0;  ",
+            "  int f(); int f;  ",
+            "  extern int f; int f(void);  ",
+            "  int f(void); extern int f;  ",
+        ] {
+            let mut parser = Parser::new(text);
+            let error = parser.parse().unwrap_err().to_string();
+            assert!(error.contains("redefinition of 'f'"), "{}", text);
+        }
+    }
+
+    #[test]
+    fn incompatible_pointer_arguments() {
+        let mut parser = Parser::new(
+            "  int f(const void *p); int g(const volatile int *p); int h(int **p); int *p; const int *q; f(p); f(q); g(p); g(q); h(&p); h(0);  ",
+        );
+        parser.parse().unwrap();
+
+        for text in [
+            "  int f(int *p) { return 0; } double d; f(&d);  ",
+            "  int f(int *p); const int *q; f(q);  ",
+            "  int f(void *p); volatile int *q; f(q);  ",
+            "  int f(const int **p); int *p; f(&p);  ",
+        ] {
+            let mut parser = Parser::new(text);
+            let error = parser.parse().unwrap_err().to_string();
+            assert!(
+                error.contains("incompatible type for argument 1"),
+                "{}",
+                text
+            );
+        }
+    }
+
+    #[test]
+    fn integer_constants_beyond_int() {
+        let mut parser = Parser::new("  2147483647; 2147483648;  ");
+        let program = parser.parse().unwrap();
+        let Node::Block { statements } = &program.functions[0].body else {
+            panic!("main is not a block");
+        };
+        assert_eq!(statements[0], Node::Integer { value: 2147483647 });
+        assert_eq!(statements[1].ty(), Type::Long);
+        assert_eq!(statements[1].evaluate_long_constant(), Some(2147483648));
+
+        let mut parser = Parser::new("  long x = 9223372036854775808;  ");
+        let error = parser.parse().unwrap_err().to_string();
+        assert!(error.contains("integer constant is too large"));
+    }
+
+    #[test]
+    fn incomplete_parameters_and_return_types() {
+        let mut parser = Parser::new(
+            "  struct S; int g(struct S); void h(void); struct S { int a; }; int g(struct S s) { return s.a; } void h(void) {}  ",
+        );
+        parser.parse().unwrap();
+
+        for text in [
+            "  struct S; int f(struct S s) { return 1; }  ",
+            "  struct S; struct S f() {}  ",
+        ] {
+            let mut parser = Parser::new(text);
+            let error = parser.parse().unwrap_err().to_string();
+            assert!(error.contains("incomplete type 'struct S'"), "{}", text);
+        }
+    }
+
+    #[test]
+    fn dereferenced_void_pointer() {
+        let mut parser = Parser::new("  void *p; *p; int i = *p;  ");
+        assert!(parser.parse().is_err());
+    }
+
+    #[test]
+    fn return_without_value() {
+        let mut parser = Parser::new("  void f(int x) { if (x) return; x = 1; }  ");
+        let function = &parser.parse().unwrap().functions[1];
+        let Node::Block { statements } = &function.body else {
+            panic!("function bodies are blocks");
+        };
+        assert!(matches!(
+            &statements[0],
+            Node::If { statement, .. } if **statement == Node::Return { value: None }
+        ));
+
+        for text in [
+            "  int f() { return; }  ",
+            "  void f() {} int x = f();  ",
+            "  void f() {} int x; x = f();  ",
+            "  void f() {} int g() { return f(); }  ",
+            "  void f() {} int g(); g(f());  ",
+        ] {
+            let mut parser = Parser::new(text);
+            assert!(parser.parse().is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn va_arg_of_non_scalar_types() {
+        for ty in ["struct P", "struct Q", "int[2]", "int()", "void"] {
+            let text = format!(
+                "  struct P {{ int x; }}; int f(int n, ...) {{ va_list ap; va_start(ap, n); va_arg(ap, {}); return 0; }}  ",
+                ty
+            );
+            let mut parser = Parser::new(&text);
+            assert!(parser.parse().is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn stack_size_rounded_to_16() {
+        let mut parser = Parser::new("  { int a; int b; } { int c; int d; int e; }  ");
+        assert_eq!(parser.parse().unwrap().functions[0].stack_size, 32);
+    }
+
+    #[test]
+    fn static_locals_in_different_functions() {
+        let mut parser = Parser::new(
+            "  int f() { static int n = 3; return n; } int g() { static int n; return n; }  ",
+        );
+        let variables = parser.parse().unwrap().variables;
+        assert_eq!(
+            variables,
+            vec![
+                VariableDefinition {
+                    label: String::from(".Lstatic.f.n.1"),
+                    ty: Type::Int,
+                    is_static: true,
+                    is_thread_local: false,
+                    data: Some(vec![3, 0, 0, 0]),
+                    relocations: vec![],
+                },
+                VariableDefinition {
+                    label: String::from(".Lstatic.g.n.2"),
+                    ty: Type::Int,
+                    is_static: true,
+                    is_thread_local: false,
+                    data: None,
+                    relocations: vec![],
+                },
+            ]
+        )
+    }
+
+    #[test]
+    fn static_local_with_non_constant_initializer() {
+        let mut parser = Parser::new("  int f(int a) { static int n = a; return n; }  ");
+        assert!(parser.parse().is_err());
+    }
+
+    #[test]
+    fn static_function() {
+        let mut parser = Parser::new("  static int f() { return 1; } f();  ");
+        let functions = parser.parse().unwrap().functions;
+        assert!(functions[1].is_static);
+    }
+
+    #[test]
+    fn extern_declaration() {
+        let mut parser = Parser::new("  extern double d; d;  ");
+        let program = parser.parse().unwrap();
+        assert!(program.variables.is_empty());
+        assert_eq!(
+            program.functions[0].body,
+            Node::Block {
+                statements: vec![
+                    Node::Block { statements: vec![] },
+                    Node::GlobalVariable {
+                        identifier: String::from("d"),
+                        label: String::from("d"),
+                        ty: Type::Double,
+                    }
+                ]
+            }
+        )
+    }
+
+    #[test]
+    fn extern_declaration_before_definition() {
+        let mut parser = Parser::new("  extern int a; int a = 1; extern int a;  ");
+        assert_eq!(parser.parse().unwrap().variables.len(), 1);
+    }
+
+    #[test]
+    fn global_with_non_constant_initializer_and_main() {
+        let mut parser = Parser::new("  int a = 1; int b = a; int main() { return b; }  ");
+        assert!(parser.parse().is_err());
+    }
+
+    #[test]
+    fn floating_global() {
+        let mut parser = Parser::new("  float f = 1.5;  ");
+        assert_eq!(
+            parser.parse().unwrap().variables[0].data,
+            Some(1.5f32.to_le_bytes().to_vec())
+        )
+    }
+
+    #[test]
+    fn bool_global() {
+        let mut parser = Parser::new("  _Bool a = 256; _Bool b = 0.5; _Bool c = 0.0;  ");
+        let variables = parser.parse().unwrap().variables;
+        assert_eq!(variables[0].data, Some(vec![1]));
+        assert_eq!(variables[1].data, Some(vec![1]));
+        assert_eq!(variables[2].data, Some(vec![0]));
+    }
+
+    #[test]
+    fn assignment_to_const() {
+        for text in [
+            "  const int x = 1; x = 2;  ",
+            "  int f(const char *s) { *s = 1; return 0; }  ",
+            "  char *const p = 0; p = 0;  ",
+            "  struct { int x; } const s = { 1 }; s.x = 2;  ",
+            "  const int a[2] = { 1, 2 }; a[0] = 3;  ",
+        ] {
+            let mut parser = Parser::new(text);
+            assert!(parser.parse().is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn assignment_to_struct_with_const_member() {
+        let mut parser = Parser::new(
+            "  struct P { const int x; int y; } p, q; struct O { struct P p; int z; } o; p.y = 1; o.p.y = 2; o.z = 3;  ",
+        );
+        parser.parse().unwrap();
+
+        for text in [
+            "  struct P { const int x; int y; } p, q; p = q;  ",
+            "  struct P { const int x; } p; struct O { struct P p; } o, r; o = r;  ",
+            "  struct P { const int x; } p; struct O { struct P a[2]; } o, r; o = r;  ",
+            "  struct P { const int x; } p, q; struct O { struct P p; } o; o.p = q;  ",
+        ] {
+            let mut parser = Parser::new(text);
+            let error = parser.parse().unwrap_err().to_string();
+            assert!(error.contains("assignment of read-only"), "{}", text);
+        }
+    }
+
+    #[test]
+    fn struct_returned_in_memory() {
+        let mut parser = Parser::new(
+            "  struct L { long a[3]; } f(long x) { struct L s; return s; } struct P { int a; } g() { struct P p; return p; }  ",
+        );
+        let functions = parser.parse().unwrap().functions;
+        let f = functions.iter().find(|f| f.name == "f").unwrap();
+        assert_eq!(f.parameters.len(), 2);
+        assert!(f.parameters[0].ty().is_pointer());
+        let g = functions.iter().find(|f| f.name == "g").unwrap();
+        assert!(g.parameters.is_empty());
+    }
+
+    #[test]
+    fn struct_initializer_expression() {
+        let mut parser = Parser::new("  struct P { int a; } p; struct P q = p;  ");
+        assert!(parser.parse().is_ok());
+        let mut parser = Parser::new("  struct P { int a; } p = 1;  ");
+        assert!(parser.parse().is_err());
+    }
+
+    #[test]
+    fn prototype_conversions() {
+        let mut parser = Parser::new("  int f(double x, char *p, _Bool b); f(1, 0, f);  ");
+        let statements = match parser.parse().unwrap().functions.remove(0).body {
+            Node::Block { statements } => statements,
+            _ => unreachable!(),
+        };
+        let Some(Node::FunctionCall { arguments, ty, .. }) = statements.last() else {
+            panic!("{:?}", statements)
+        };
+        assert_eq!(*ty, Type::Int);
+        assert_eq!(
+            arguments.iter().map(Node::ty).collect::<Vec<_>>(),
+            vec![Type::Double, Type::pointer_to(Type::Char), Type::Bool]
+        );
+    }
+
+    #[test]
+    fn argument_checks() {
+        for text in [
+            "  int f(int a); f();  ",
+            "  int f(int a); f(1, 2);  ",
+            "  int f(int a, ...); f();  ",
+            "  int f(void); f(1);  ",
+            "  int f(char *p); f(1);  ",
+            "  int f(int *p); f(1.5);  ",
+            "  struct S { int a; } s; int f(int x); f(s);  ",
+            "  int f(int (*g)(int)); f(1);  ",
+        ] {
+            let mut parser = Parser::new(text);
+            assert!(parser.parse().is_err(), "{}", text);
+        }
+        for text in [
+            "  int f(); f(1, 2.5);  ",
+            "  int f(int a, ...); f(1, 2, 3);  ",
+            "  int f(void *p); char *s = \"a\"; f(s);  ",
+        ] {
+            let mut parser = Parser::new(text);
+            assert!(parser.parse().is_ok(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn implicit_function_declaration() {
+        let mut parser = Parser::new("  f(1); f(2); int g(int x); g(3);  ");
+        parser.parse().unwrap();
+        assert_eq!(
+            parser.take_warnings(),
+            vec![CompileWarning::implicit_function_declaration(
+                2,
+                String::from("f")
+            )]
+        );
+    }
+
+    #[test]
+    fn conflicting_function_declarations() {
+        for text in [
+            "  int f(int *); int f(int a[]); int f(); int f(int *a) { return *a; }  ",
+            "  struct T; int f(struct T *); struct T { int x; }; int f(struct T *t) { return t->x; }  ",
+        ] {
+            let mut parser = Parser::new(text);
+            assert!(parser.parse().is_ok(), "{}", text);
+        }
+        for text in [
+            "  int f(int a) { return a * 2; } double f(int a);  ",
+            "  int f(int); int f(double);  ",
+            "  int f(int); int f(int, int);  ",
+            "  int f(); int f(char a);  ",
+            "  int f(); int f(float a);  ",
+            "  int f(int, ...); int f();  ",
+            "  int f(const int *); int f(int *);  ",
+            "  g(); double g();  ",
+        ] {
+            let mut parser = Parser::new(text);
+            assert!(parser.parse().is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn bit_field_layout() {
+        let mut parser = Parser::new(
+            "  struct { char a; int b : 5; int c : 20; long d : 40; int : 0; char e; unsigned : 7; unsigned f : 30; } s;  ",
+        );
+        parser.parse().unwrap();
+        let Some(Variable::Global { ty, .. }) = parser.get_variable_by_name("s") else {
+            unreachable!()
+        };
+        let Type::Struct { members, size, .. } = ty else {
+            unreachable!()
+        };
+        let layout = members
+            .iter()
+            .map(|member| {
+                let bit_field = member.bit_field.map(|b| (b.bit_offset, b.width));
+                (member.name.as_str(), member.offset, bit_field)
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            layout,
+            vec![
+                ("a", 0, None),
+                ("b", 0, Some((8, 5))),
+                ("c", 4, Some((0, 20))),
+                ("d", 8, Some((0, 40))),
+                ("e", 16, None),
+                ("f", 20, Some((0, 30))),
+            ]
+        );
+        assert_eq!(size, 24);
+    }
+
+    #[test]
+    fn bit_field_promotion() {
+        let mut parser = Parser::new(
+            "  struct S { unsigned a : 3; unsigned long b : 31; unsigned c : 32; long d : 40; } s; s.a - 8; s.b - 8; s.c - 8; s.d - 8;  ",
+        );
+        let function = &parser.parse().unwrap().functions[0];
+        let Node::Block { statements } = &function.body else {
+            panic!("function bodies are blocks");
+        };
+        let types: Vec<_> = statements[1..].iter().map(|node| node.ty()).collect();
+        assert_eq!(
+            types,
+            vec![Type::Int, Type::Int, Type::UnsignedInt, Type::Long]
+        );
+    }
+
+    #[test]
+    fn invalid_bit_fields() {
+        for text in [
+            "  struct { int a : 33; } s;  ",
+            "  struct { int a : 0; } s;  ",
+            "  struct { double a : 1; } s;  ",
+            "  struct { int a : -1; } s;  ",
+            "  struct { int a : 1; } s; &s.a;  ",
+        ] {
+            let mut parser = Parser::new(text);
+            assert!(parser.parse().is_err(), "{}", text);
+        }
     }
 
     #[test]
-    fn unary_minus() {
-        let mut parser = Parser::new("  1 * - 2 ;  ");
+    fn self_referential_struct() {
+        let mut parser = Parser::new("  struct S { int v; struct S *next; } s; s.next->next->v;  ");
+        parser.parse().unwrap();
+        let Some(Type::Struct { members, size, .. }) = parser.get_tag_by_name("S") else {
+            unreachable!()
+        };
         assert_eq!(
-            parser.consume_statement().unwrap(),
-            Node::OperatorMul {
-                lhs: Box::new(Node::Integer { value: 1 }),
-                rhs: Box::new(Node::OperatorSub {
-                    lhs: Box::new(Node::Integer { value: 0 }),
-                    rhs: Box::new(Node::Integer { value: 2 }),
-                }),
-            }
+            members[1].ty,
+            Type::pointer_to(Type::IncompleteStruct {
+                tag: String::from("S")
+            })
         );
-        assert!(parser.next_eof().is_some());
+        assert_eq!(size, 16);
     }
 
     #[test]
-    fn lt_gt() {
-        let mut parser = Parser::new("  1 < 2 > 3 ;  ");
-        assert_eq!(
-            parser.consume_statement().unwrap(),
-            Node::OperatorLt {
-                lhs: Box::new(Node::Integer { value: 3 }),
-                rhs: Box::new(Node::OperatorLt {
-                    lhs: Box::new(Node::Integer { value: 1 }),
-                    rhs: Box::new(Node::Integer { value: 2 }),
-                }),
-            }
-        )
+    fn flexible_array_member() {
+        let mut parser = Parser::new("  struct S { char c; long data[]; } s;  ");
+        parser.parse().unwrap();
+        let Some(Type::Struct { members, size, .. }) = parser.get_tag_by_name("S") else {
+            unreachable!()
+        };
+        assert_eq!(members[1].offset, 8);
+        assert_eq!(size, 8);
     }
 
     #[test]
-    fn lteq_gteq() {
-        let mut parser = Parser::new("  1 <= 2 >= 3 ;  ");
-        assert_eq!(
-            parser.consume_statement().unwrap(),
-            Node::OperatorLtEq {
-                lhs: Box::new(Node::Integer { value: 3 }),
-                rhs: Box::new(Node::OperatorLtEq {
-                    lhs: Box::new(Node::Integer { value: 1 }),
-                    rhs: Box::new(Node::Integer { value: 2 }),
-                }),
-            }
-        )
+    fn incomplete_types() {
+        for text in [
+            "  struct S; struct S s;  ",
+            "  struct S *p; p->x;  ",
+            "  struct S *p; *p;  ",
+            "  struct S *p; p + 1;  ",
+            "  struct S; struct S a[2];  ",
+            "  struct S { struct S s; };  ",
+            "  struct S { int data[]; } s;  ",
+            "  struct S { int data[]; int n; } s;  ",
+            "  struct S { int n; int data[]; } s = { 1, { 2 } };  ",
+            "  int a[];  ",
+        ] {
+            let mut parser = Parser::new(text);
+            assert!(parser.parse().is_err(), "{}", text);
+        }
     }
 
     #[test]
-    fn eq_ne() {
-        let mut parser = Parser::new("  1 == 2 != 3 ;  ");
+    fn qualified_declarations() {
+        let mut parser = Parser::new("  const char *const volatile p = 0; int const *q; p;  ");
+        let variables = parser.parse().unwrap().variables;
         assert_eq!(
-            parser.consume_statement().unwrap(),
-            Node::OperatorNe {
-                lhs: Box::new(Node::OperatorEq {
-                    lhs: Box::new(Node::Integer { value: 1 }),
-                    rhs: Box::new(Node::Integer { value: 2 }),
-                }),
-                rhs: Box::new(Node::Integer { value: 3 })
-            }
-        )
+            variables[0].ty,
+            Type::qualified(
+                Type::pointer_to(Type::qualified(Type::Char, true, false)),
+                true,
+                true
+            )
+        );
+        assert_eq!(
+            variables[1].ty,
+            Type::pointer_to(Type::qualified(Type::Int, true, false))
+        );
     }
 
     #[test]
-    fn assignment() {
-        let mut parser = Parser::new("  foo = bar + 2 ;  ");
-        assert_eq!(
-            parser.consume_statement().unwrap(),
-            Node::OperatorAssign {
-                lhs: Box::new(Node::LocalVariable {
-                    identifier: String::from("foo"),
-                    offset: 8
-                }),
-                rhs: Box::new(Node::OperatorAdd {
-                    lhs: Box::new(Node::LocalVariable {
-                        identifier: String::from("bar"),
-                        offset: 16
-                    }),
-                    rhs: Box::new(Node::Integer { value: 2 }),
-                }),
-            }
-        )
+    fn array_completed_by_initializer() {
+        let mut parser = Parser::new("  short a[] = { 1, [3] = 2 };  ");
+        let variable = &parser.parse().unwrap().variables[0];
+        assert_eq!(variable.ty, Type::array_of(Type::Short, Some(4)));
+        assert_eq!(variable.data, Some(vec![1, 0, 0, 0, 0, 0, 2, 0]));
     }
 
     #[test]
-    fn multiple_statements() {
-        let mut parser = Parser::new("  1 + 2; a;  ");
+    fn struct_initializer_with_designators() {
+        let mut parser =
+            Parser::new("  struct { char c; int i; } s[2] = { [1].c = 2, 3, [0].i = 4 };  ");
         assert_eq!(
-            parser.parse().unwrap(),
-            Node::Block {
-                statements: vec![
-                    Node::OperatorAdd {
-                        lhs: Box::new(Node::Integer { value: 1 }),
-                        rhs: Box::new(Node::Integer { value: 2 })
-                    },
-                    Node::LocalVariable {
-                        identifier: String::from("a"),
-                        offset: 8
-                    }
-                ]
-            }
+            parser.parse().unwrap().variables[0].data,
+            Some(vec![0, 0, 0, 0, 4, 0, 0, 0, 2, 0, 0, 0, 3, 0, 0, 0])
         )
     }
 
     #[test]
-    fn return_statement() {
-        let mut parser = Parser::new("  return 1 + 2;  ");
-        assert_eq!(
-            parser.consume_statement().unwrap(),
-            Node::Return {
-                value: Box::new(Node::OperatorAdd {
-                    lhs: Box::new(Node::Integer { value: 1 }),
-                    rhs: Box::new(Node::Integer { value: 2 })
-                }),
-            }
-        )
+    fn excess_initializer() {
+        let mut parser = Parser::new("  int a[2] = { 1, 2, 3 };  ");
+        assert!(parser.parse().is_err());
     }
 
     #[test]
-    fn if_statement() {
-        let mut parser = Parser::new("  if ( 1 < 2 ) 1;  ");
-        assert_eq!(
-            parser.consume_statement().unwrap(),
-            Node::If {
-                condition: Box::new(Node::OperatorLt {
-                    lhs: Box::new(Node::Integer { value: 1 }),
-                    rhs: Box::new(Node::Integer { value: 2 })
-                }),
-                statement: Box::new(Node::Integer { value: 1 }),
-                end_label: String::from(".Lend1"),
-            }
-        )
+    fn string_initializer() {
+        let mut parser = Parser::new("  char s[4] = \"a\\tb\"; char t[3] = \"abc\";  ");
+        let variables = parser.parse().unwrap().variables;
+        assert_eq!(variables[0].data, Some(vec![b'a', b'\t', b'b', 0]));
+        assert_eq!(variables[1].data, Some(b"abc".to_vec()));
     }
 
     #[test]
-    fn if_else_statement() {
-        let mut parser = Parser::new("  if ( 1 < 2 ) 1; else 2;  ");
+    fn address_initializer() {
+        let mut parser = Parser::new("  int a[4]; int *p = &a[2]; char *s = \"x\";  ");
+        let variables = parser.parse().unwrap().variables;
         assert_eq!(
-            parser.consume_statement().unwrap(),
-            Node::IfElse {
-                condition: Box::new(Node::OperatorLt {
-                    lhs: Box::new(Node::Integer { value: 1 }),
-                    rhs: Box::new(Node::Integer { value: 2 })
-                }),
-                statement: Box::new(Node::Integer { value: 1 }),
-                end_label: String::from(".Lend1"),
-                else_statement: Box::new(Node::Integer { value: 2 }),
-                else_label: String::from(".Lelse1"),
-            }
-        )
+            variables[1].relocations,
+            vec![Relocation {
+                offset: 0,
+                label: String::from("a"),
+                addend: 8,
+            }]
+        );
+        assert_eq!(variables[2].data, Some(vec![b'x', 0]));
+        assert_eq!(variables[3].relocations[0].label, variables[2].label);
     }
 
     #[test]
-    fn while_statement() {
-        let mut parser = Parser::new("  while ( 1 < 2 ) 1;  ");
+    fn initializer_referring_to_its_variable() {
+        let mut parser = Parser::new(
+            "  struct N { struct N *p; int v; } n = { &n, 5 }; void *a[] = { 0, a };  ",
+        );
+        let variables = parser.parse().unwrap().variables;
+        assert_eq!(variables[0].relocations[0].label, "n");
         assert_eq!(
-            parser.consume_statement().unwrap(),
-            Node::While {
-                condition: Box::new(Node::OperatorLt {
-                    lhs: Box::new(Node::Integer { value: 1 }),
-                    rhs: Box::new(Node::Integer { value: 2 })
-                }),
-                statement: Box::new(Node::Integer { value: 1 }),
-                begin_label: String::from(".Lbegin1"),
-                end_label: String::from(".Lend1"),
-            }
-        )
+            variables[1].ty,
+            Type::array_of(Type::pointer_to(Type::Void), Some(2))
+        );
+        assert_eq!(variables[1].relocations[0].label, "a");
     }
 
     #[test]
-    fn for_statement() {
-        let mut parser = Parser::new("  for ( i = 0; i < 10; i = i + 1 ) i;  ");
-        assert_eq!(
-            parser.consume_statement().unwrap(),
-            Node::For {
-                initializer: Some(Box::new(Node::OperatorAssign {
-                    lhs: Box::new(Node::LocalVariable {
-                        identifier: String::from("i"),
-                        offset: 8
-                    }),
-                    rhs: Box::new(Node::Integer { value: 0 }),
-                })),
-                condition: Some(Box::new(Node::OperatorLt {
-                    lhs: Box::new(Node::LocalVariable {
-                        identifier: String::from("i"),
-                        offset: 8
-                    }),
-                    rhs: Box::new(Node::Integer { value: 10 }),
-                })),
-                updater: Some(Box::new(Node::OperatorAssign {
-                    lhs: Box::new(Node::LocalVariable {
-                        identifier: String::from("i"),
-                        offset: 8
-                    }),
-                    rhs: Box::new(Node::OperatorAdd {
-                        lhs: Box::new(Node::LocalVariable {
-                            identifier: String::from("i"),
-                            offset: 8
-                        }),
-                        rhs: Box::new(Node::Integer { value: 1 })
-                    })
-                })),
-                statement: Box::new(Node::LocalVariable {
-                    identifier: String::from("i"),
-                    offset: 8
-                }),
-                begin_label: String::from(".Lbegin1"),
-                end_label: String::from(".Lend1")
-            }
-        )
+    fn compound_literal_at_file_scope() {
+        let mut parser = Parser::new("  int *p = (int[]){ 1, 2 };  ");
+        let variables = parser.parse().unwrap().variables;
+        assert_eq!(variables[0].ty, Type::array_of(Type::Int, Some(2)));
+        assert_eq!(variables[0].data, Some(vec![1, 0, 0, 0, 2, 0, 0, 0]));
+        assert_eq!(variables[1].relocations[0].label, variables[0].label);
     }
 
     #[test]
-    fn for_statement_abbr() {
-        let mut parser = Parser::new("  for(;;) 1;  ");
+    fn local_initializer() {
+        let mut parser = Parser::new("  int f() { int a[2] = { 1 }; return a[1]; }  ");
+        let function = &parser.parse().unwrap().functions[1];
+        let Node::Block { statements } = &function.body else {
+            panic!("function bodies are blocks");
+        };
+        let target = Node::LocalVariable {
+            identifier: String::from("a"),
+            offset: 8,
+            ty: Type::array_of(Type::Int, Some(2)),
+        };
         assert_eq!(
-            parser.consume_statement().unwrap(),
-            Node::For {
-                initializer: None,
-                condition: None,
-                updater: None,
-                statement: Box::new(Node::Integer { value: 1 }),
-                begin_label: String::from(".Lbegin1"),
-                end_label: String::from(".Lend1")
+            statements[0],
+            Node::Block {
+                statements: vec![
+                    Node::ZeroFill {
+                        target: target.to_owned().into(),
+                    },
+                    Parser::new_assign(Parser::new_element(target, 0), Node::Integer { value: 1 }),
+                ]
             }
-        )
+        );
     }
 
     #[test]
-    fn block() {
-        let mut parser = Parser::new("  { 1; }  ");
-        assert_eq!(
-            parser.consume_statement().unwrap(),
-            Node::Block {
-                statements: vec![Node::Integer { value: 1 }],
-            }
-        )
+    fn extern_declarations_with_alignment() {
+        let mut parser = Parser::new(
+            "  _Alignas(16) int x; extern int x; extern _Alignas(32) int y; int y; extern long y;  ",
+        );
+        assert!(parser.parse().is_err());
+        let mut parser =
+            Parser::new("  _Alignas(16) int x; extern int x; extern _Alignas(32) int y; int y;  ");
+        let variables = parser.parse().unwrap().variables;
+        assert_eq!(variables[0].ty, Type::aligned(Type::Int, 16));
+        assert_eq!(variables[1].ty, Type::aligned(Type::Int, 32));
     }
 
     #[test]
-    fn if_statement_with_block() {
-        let mut parser = Parser::new("  if(a < 0) { a = 0; a = 1; }  ");
-        assert_eq!(
-            parser.consume_statement().unwrap(),
-            Node::If {
-                condition: Box::new(Node::OperatorLt {
-                    lhs: Box::new(Node::LocalVariable {
-                        identifier: String::from("a"),
-                        offset: 8
-                    }),
-                    rhs: Box::new(Node::Integer { value: 0 })
-                }),
-                statement: Box::new(Node::Block {
-                    statements: vec![
-                        Node::OperatorAssign {
-                            lhs: Box::new(Node::LocalVariable {
-                                identifier: String::from("a"),
-                                offset: 8
-                            }),
-                            rhs: Box::new(Node::Integer { value: 0 })
-                        },
-                        Node::OperatorAssign {
-                            lhs: Box::new(Node::LocalVariable {
-                                identifier: String::from("a"),
-                                offset: 8
-                            }),
-                            rhs: Box::new(Node::Integer { value: 1 })
-                        }
-                    ]
-                }),
-                end_label: String::from(".Lend1"),
-            }
-        )
+    fn aligned_declarations() {
+        let mut parser =
+            Parser::new("  struct S { char c; _Alignas(16) int x; } s; _Alignas(long) char *p;  ");
+        parser.parse().unwrap();
+        let Some(Type::Struct {
+            members,
+            size,
+            align,
+        }) = parser.get_tag_by_name("S")
+        else {
+            unreachable!()
+        };
+        assert_eq!(members[1].offset, 16);
+        assert_eq!((size, align), (32, 16));
+
+        let mut scope = VariableScope::new(0);
+        let Some(Variable::Local { offset, .. }) =
+            scope.insert_variable(String::from("a"), Type::aligned(Type::Char, 16))
+        else {
+            unreachable!()
+        };
+        assert_eq!(offset, 16);
+        let Some(Variable::Local { offset, .. }) =
+            scope.insert_variable(String::from("b"), Type::aligned(Type::Int, 64))
+        else {
+            unreachable!()
+        };
+        assert_eq!((offset, scope.last_offset), (32, 80));
     }
 
     #[test]
-    fn function_call() {
-        let mut parser = Parser::new("  func();  ");
-        assert_eq!(
-            parser.consume_statement().unwrap(),
-            Node::FunctionCall {
-                identifier: String::from("func"),
-                arguments: vec![]
-            }
-        )
+    fn invalid_alignments() {
+        for text in [
+            "  _Alignas(3) int x;  ",
+            "  _Alignas(2) int x;  ",
+            "  _Alignas(-8) int x;  ",
+            "  _Alignas(char) long x;  ",
+            "  _Alignof(struct T);  ",
+        ] {
+            let mut parser = Parser::new(text);
+            assert!(parser.parse().is_err(), "{}", text);
+        }
     }
 
     #[test]
-    fn function_call_with_arguments() {
-        let mut parser = Parser::new("  func(1, 2);  ");
-        assert_eq!(
-            parser.consume_statement().unwrap(),
-            Node::FunctionCall {
-                identifier: String::from("func"),
-                arguments: vec![Node::Integer { value: 1 }, Node::Integer { value: 2 },]
-            }
-        )
+    fn thread_local_variables() {
+        let mut parser = Parser::new(
+            "  _Thread_local int a = 1; int f() { static __thread int b; return a + b; }  ",
+        );
+        let program = parser.parse().unwrap();
+        assert!(program
+            .variables
+            .iter()
+            .all(|variable| variable.is_thread_local));
+        let Some(Variable::Global {
+            is_thread_local: true,
+            ..
+        }) = parser.get_variable_by_name("a")
+        else {
+            unreachable!()
+        };
+
+        for text in [
+            "  int f() { _Thread_local int x; return x; }  ",
+            "  _Thread_local int f();  ",
+            "  static extern int x;  ",
+            "  __thread _Thread_local int x;  ",
+            "  int g; _Thread_local int x = g;  ",
+            "  extern int x; _Thread_local int x;  ",
+        ] {
+            let mut parser = Parser::new(text);
+            assert!(parser.parse().is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn variable_length_arrays() {
+        let mut parser = Parser::new("  int f(int n) { int a[n][2]; return sizeof a; }  ");
+        let function = &parser.parse().unwrap().functions[1];
+        let Node::Block { statements } = &function.body else {
+            panic!("function bodies are blocks");
+        };
+        assert!(matches!(
+            statements.last(),
+            Some(Node::RestoreStackPointer { .. })
+        ));
+        let Node::Block {
+            statements: declaration,
+        } = &statements[0]
+        else {
+            panic!("declarations are blocks");
+        };
+        let [Node::OperatorAssign { .. }, Node::SaveStackPointer { .. }, Node::OperatorAssign { rhs, .. }] =
+            &declaration[..]
+        else {
+            panic!("sizes are computed before the stack pointer is saved");
+        };
+        assert!(
+            matches!(rhs.as_ref(), Node::Cast { value, .. } if matches!(value.as_ref(), Node::Alloca { .. }))
+        );
+
+        let mut parser = Parser::new("  int f(int n) { return sizeof(int[n][3]); }  ");
+        let function = &parser.parse().unwrap().functions[1];
+        let Node::Block { statements } = &function.body else {
+            panic!("function bodies are blocks");
+        };
+        let [Node::Return { value: Some(value) }] = &statements[..] else {
+            panic!("type names do not declare anything");
+        };
+        let Node::Cast { value, .. } = value.as_ref() else {
+            panic!("sizes are converted to the return type");
+        };
+        assert!(
+            matches!(value.as_ref(), Node::VariableArraySizes { sizes, .. } if sizes.len() == 1)
+        );
+
+        for text in [
+            "  int n = 3; int a[n];  ",
+            "  int f(int n) { static int a[n]; return 0; }  ",
+            "  int f(int n) { int a[n] = {1}; return 0; }  ",
+            "  int f(int n) { struct { int a[n]; } s; return 0; }  ",
+            "  int f(int n) { int a[-1]; return 0; }  ",
+            "  int f(int n) { goto in; { int a[n]; in: n = 1; } return 0; }  ",
+            "  int f(int n) { { int a[n]; back: n = 1; } goto back; return 0; }  ",
+            "  int f(int n) { { int a[n]; in: n = 1; } { int b[n]; goto in; } return 0; }  ",
+            "  int f(int n) { switch (n) { int a[n]; case 3: n = 1; } return 0; }  ",
+            "  int f(int n) { switch (n) { case 1: { int a[n]; default: n = 1; } } return 0; }  ",
+        ] {
+            let mut parser = Parser::new(text);
+            assert!(parser.parse().is_err(), "{}", text);
+        }
+    }
+
+    #[test]
+    fn generic_selection() {
+        let mut parser = Parser::new(
+            "  const char c; int a[2]; _Generic(c, char: 1, default: 2); _Generic(a, int *: 3, int: 4);  ",
+        );
+        let function = &parser.parse().unwrap().functions[0];
+        let Node::Block { statements } = &function.body else {
+            panic!("function bodies are blocks");
+        };
+        assert_eq!(statements[2], Node::Integer { value: 1 });
+        assert_eq!(statements[3], Node::Integer { value: 3 });
+
+        let mut parser = Parser::new(
+            "  signed char c; long l; _Generic(c, char: 1, signed char: 2); _Generic(l, long long: 3, long: 4);  ",
+        );
+        let function = &parser.parse().unwrap().functions[0];
+        let Node::Block { statements } = &function.body else {
+            panic!("function bodies are blocks");
+        };
+        assert_eq!(statements[2], Node::Integer { value: 2 });
+        assert_eq!(statements[3], Node::Integer { value: 4 });
+
+        for text in [
+            "  _Generic(1, int: 1, int: 2);  ",
+            "  _Generic(1, char: 1, signed char: 2, signed char: 3);  ",
+            "  _Generic(1, long long: 1, long int long: 2);  ",
+            "  _Generic(1, default: 1, default: 2);  ",
+            "  _Generic(1, long: 1);  ",
+            "  struct S; _Generic(1, struct S: 1, default: 2);  ",
+        ] {
+            let mut parser = Parser::new(text);
+            assert!(parser.parse().is_err(), "{}", text);
+        }
     }
 }
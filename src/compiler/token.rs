@@ -1,16 +1,49 @@
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, PartialEq)]
 pub enum TokenKind {
-    Integer(i32),
+    Integer(i64),
+    // integer constants beyond the range of long
+    IntegerTooLarge,
+    Float(f32),
+    Double(f64),
     Identifier(String),
+    // contents of a string literal with escape sequences resolved
+    String(Vec<u8>),
     KeywordReturn,
     KeywordIf,
     KeywordElse,
     KeywordWhile,
     KeywordFor,
+    KeywordSwitch,
+    KeywordCase,
+    KeywordDefault,
+    KeywordBreak,
+    KeywordGoto,
+    KeywordChar,
+    KeywordShort,
+    KeywordInt,
+    KeywordLong,
+    KeywordSigned,
+    KeywordUnsigned,
+    KeywordFloat,
+    KeywordDouble,
+    KeywordVoid,
+    KeywordVaList,
+    KeywordBool,
+    KeywordConst,
+    KeywordVolatile,
+    KeywordStatic,
+    KeywordExtern,
+    KeywordThreadLocal,
+    KeywordStruct,
+    KeywordAlignas,
+    KeywordAlignof,
+    KeywordSizeof,
+    KeywordGeneric,
     SymbolPlus,
     SymbolMinus,
     SymbolStar,
     SymbolSlash,
+    SymbolAmpersand,
     SymbolRoundBracketLeft,
     SymbolRoundBracketRight,
     SymbolAngleBracketLeft,
@@ -19,15 +52,45 @@ pub enum TokenKind {
     SymbolAngleBracketRightAndEqual,
     SymbolCurlyBracketLeft,
     SymbolCurlyBracketRight,
+    SymbolSquareBracketLeft,
+    SymbolSquareBracketRight,
+    SymbolDot,
+    SymbolArrow,
     SymbolDoubleEqual,
     SymbolExclamationAndEqual,
     SymbolEqual,
+    SymbolColon,
     SymbolSemicolon,
     SymbolComma,
-    EOF,
+    SymbolEllipsis,
+    Eof,
 }
 
-#[derive(Debug)]
+impl TokenKind {
+    pub fn is_type_qualifier(&self) -> bool {
+        matches!(self, Self::KeywordConst | Self::KeywordVolatile)
+    }
+
+    pub fn is_type_specifier(&self) -> bool {
+        matches!(
+            self,
+            Self::KeywordChar
+                | Self::KeywordShort
+                | Self::KeywordInt
+                | Self::KeywordLong
+                | Self::KeywordSigned
+                | Self::KeywordUnsigned
+                | Self::KeywordFloat
+                | Self::KeywordDouble
+                | Self::KeywordVoid
+                | Self::KeywordVaList
+                | Self::KeywordBool
+                | Self::KeywordStruct
+        )
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Token {
     pub kind: TokenKind,
     pub index_start: usize,
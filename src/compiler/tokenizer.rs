@@ -6,12 +6,15 @@ mod re {
     use once_cell::sync::Lazy;
     use regex::bytes::Regex;
 
-    pub const INTEGER: Lazy<Regex> = Lazy::new(|| Regex::new(r"-?(0|[1-9]\d*)").unwrap());
-    pub const IDENTIFIER: Lazy<Regex> =
+    pub static INTEGER: Lazy<Regex> = Lazy::new(|| Regex::new(r"-?(0|[1-9]\d*)").unwrap());
+    pub static FLOATING: Lazy<Regex> = Lazy::new(|| {
+        Regex::new(r"((\d+\.\d*|\.\d+)([eE][+-]?\d+)?|\d+[eE][+-]?\d+)[fF]?").unwrap()
+    });
+    pub static IDENTIFIER: Lazy<Regex> =
         Lazy::new(|| Regex::new(r"[_a-zA-Z][_a-zA-Z0-9]*").unwrap());
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct Tokenizer<'a> {
     index: usize,
     text: &'a [u8],
@@ -25,7 +28,7 @@ impl<'a> Tokenizer<'a> {
         }
     }
 
-    fn skip_whitespaces(&mut self) -> () {
+    fn skip_whitespaces(&mut self) {
         while self
             .text
             .get(self.index)
@@ -46,19 +49,31 @@ impl<'a> Tokenizer<'a> {
 
     pub fn consume_char(&mut self) -> Option<Token> {
         let token = match self.text.get(self.index) {
-            None => Some(Token::new(TokenKind::EOF, self.index)),
+            None => Some(Token::new(TokenKind::Eof, self.index)),
             Some(v) => match v {
                 b'+' => Some(Token::new(TokenKind::SymbolPlus, self.index)),
                 b'-' => Some(Token::new(TokenKind::SymbolMinus, self.index)),
                 b'*' => Some(Token::new(TokenKind::SymbolStar, self.index)),
                 b'/' => Some(Token::new(TokenKind::SymbolSlash, self.index)),
+                b'&' => Some(Token::new(TokenKind::SymbolAmpersand, self.index)),
                 b'(' => Some(Token::new(TokenKind::SymbolRoundBracketLeft, self.index)),
                 b')' => Some(Token::new(TokenKind::SymbolRoundBracketRight, self.index)),
                 b'<' => Some(Token::new(TokenKind::SymbolAngleBracketLeft, self.index)),
                 b'>' => Some(Token::new(TokenKind::SymbolAngleBracketRight, self.index)),
                 b'{' => Some(Token::new(TokenKind::SymbolCurlyBracketLeft, self.index)),
                 b'}' => Some(Token::new(TokenKind::SymbolCurlyBracketRight, self.index)),
+                b'[' => Some(Token::new(TokenKind::SymbolSquareBracketLeft, self.index)),
+                b']' => Some(Token::new(TokenKind::SymbolSquareBracketRight, self.index)),
+                // a dot followed by digits starts a floating constant
+                b'.' if !self
+                    .text
+                    .get(self.index + 1)
+                    .is_some_and(|x| x.is_ascii_digit()) =>
+                {
+                    Some(Token::new(TokenKind::SymbolDot, self.index))
+                }
                 b'=' => Some(Token::new(TokenKind::SymbolEqual, self.index)),
+                b':' => Some(Token::new(TokenKind::SymbolColon, self.index)),
                 b';' => Some(Token::new(TokenKind::SymbolSemicolon, self.index)),
                 b',' => Some(Token::new(TokenKind::SymbolComma, self.index)),
                 _ => None,
@@ -72,6 +87,20 @@ impl<'a> Tokenizer<'a> {
         token
     }
 
+    pub fn consume_3_chars(&mut self) -> Option<Token> {
+        let chars = self.text.get(self.index..self.index + 3)?;
+        let token = match chars {
+            b"..." => Some(Token::new(TokenKind::SymbolEllipsis, self.index)),
+            _ => None,
+        };
+
+        if token.is_some() {
+            self.index += 3;
+        }
+
+        token
+    }
+
     pub fn consume_2_chars(&mut self) -> Option<Token> {
         let chars = (self.text.get(self.index)?, self.text.get(self.index + 1)?);
         let token = match chars {
@@ -85,6 +114,7 @@ impl<'a> Tokenizer<'a> {
             )),
             (b'=', b'=') => Some(Token::new(TokenKind::SymbolDoubleEqual, self.index)),
             (b'!', b'=') => Some(Token::new(TokenKind::SymbolExclamationAndEqual, self.index)),
+            (b'-', b'>') => Some(Token::new(TokenKind::SymbolArrow, self.index)),
             _ => None,
         };
 
@@ -95,15 +125,89 @@ impl<'a> Tokenizer<'a> {
         token
     }
 
+    // unterminated literals end the tokens as unknown characters do
+    pub fn consume_string(&mut self) -> Option<Token> {
+        let index = self.index;
+        if self.text.get(index) != Some(&b'"') {
+            return None;
+        }
+
+        let mut value = Vec::<u8>::new();
+        let mut i = index + 1;
+        loop {
+            match *self.text.get(i)? {
+                b'"' => break,
+                b'\\' => {
+                    let (byte, length) = Self::escape_sequence(self.text.get(i + 1..)?)?;
+                    value.push(byte);
+                    i += 1 + length;
+                }
+                byte => {
+                    value.push(byte);
+                    i += 1;
+                }
+            }
+        }
+        self.index = i + 1;
+
+        Some(Token::new(TokenKind::String(value), index))
+    }
+
+    // the byte of an escape sequence after its backslash and the number of bytes it takes
+    fn escape_sequence(text: &[u8]) -> Option<(u8, usize)> {
+        let byte = match *text.first()? {
+            b'a' => 0x07,
+            b'b' => 0x08,
+            b'f' => 0x0c,
+            b'n' => b'\n',
+            b'r' => b'\r',
+            b't' => b'\t',
+            b'v' => 0x0b,
+            b'0'..=b'7' => {
+                let digits = text
+                    .iter()
+                    .take(3)
+                    .take_while(|x| (b'0'..=b'7').contains(*x))
+                    .count();
+                let value = u32::from_str_radix(std::str::from_utf8(&text[..digits]).ok()?, 8);
+                return Some((value.ok()? as u8, digits));
+            }
+            b'x' => {
+                let digits = text[1..]
+                    .iter()
+                    .take_while(|x| x.is_ascii_hexdigit())
+                    .count();
+                let value =
+                    u32::from_str_radix(std::str::from_utf8(&text[1..1 + digits]).ok()?, 16);
+                return Some((value.ok()? as u8, 1 + digits));
+            }
+            byte => byte,
+        };
+
+        Some((byte, 1))
+    }
+
+    pub fn consume_floating(&mut self) -> Option<Token> {
+        let index = self.index;
+        let value = self.consume_regex(&re::FLOATING)?;
+
+        match value.strip_suffix(['f', 'F']) {
+            Some(value) => Some(Token::new(TokenKind::Float(value.parse().unwrap()), index)),
+            None => Some(Token::new(TokenKind::Double(value.parse().unwrap()), index)),
+        }
+    }
+
     pub fn consume_integer(&mut self) -> Option<Token> {
         let index = self.index;
-        let value: i32 = self.consume_regex(&*re::INTEGER)?.parse().unwrap();
-        Some(Token::new(TokenKind::Integer(value), index))
+        match self.consume_regex(&re::INTEGER)?.parse() {
+            Ok(value) => Some(Token::new(TokenKind::Integer(value), index)),
+            Err(_) => Some(Token::new(TokenKind::IntegerTooLarge, index)),
+        }
     }
 
     pub fn consume_identifier_and_keywords(&mut self) -> Option<Token> {
         let index = self.index;
-        let value = self.consume_regex(&*re::IDENTIFIER)?;
+        let value = self.consume_regex(&re::IDENTIFIER)?;
 
         match value.as_str() {
             "return" => Some(Token::new(TokenKind::KeywordReturn, index)),
@@ -111,6 +215,33 @@ impl<'a> Tokenizer<'a> {
             "else" => Some(Token::new(TokenKind::KeywordElse, index)),
             "while" => Some(Token::new(TokenKind::KeywordWhile, index)),
             "for" => Some(Token::new(TokenKind::KeywordFor, index)),
+            "switch" => Some(Token::new(TokenKind::KeywordSwitch, index)),
+            "case" => Some(Token::new(TokenKind::KeywordCase, index)),
+            "default" => Some(Token::new(TokenKind::KeywordDefault, index)),
+            "break" => Some(Token::new(TokenKind::KeywordBreak, index)),
+            "goto" => Some(Token::new(TokenKind::KeywordGoto, index)),
+            "char" => Some(Token::new(TokenKind::KeywordChar, index)),
+            "short" => Some(Token::new(TokenKind::KeywordShort, index)),
+            "int" => Some(Token::new(TokenKind::KeywordInt, index)),
+            "long" => Some(Token::new(TokenKind::KeywordLong, index)),
+            "signed" => Some(Token::new(TokenKind::KeywordSigned, index)),
+            "unsigned" => Some(Token::new(TokenKind::KeywordUnsigned, index)),
+            "float" => Some(Token::new(TokenKind::KeywordFloat, index)),
+            "double" => Some(Token::new(TokenKind::KeywordDouble, index)),
+            "void" => Some(Token::new(TokenKind::KeywordVoid, index)),
+            // there is no preprocessor to include stdarg.h, so va_list is built in
+            "va_list" => Some(Token::new(TokenKind::KeywordVaList, index)),
+            "_Bool" => Some(Token::new(TokenKind::KeywordBool, index)),
+            "const" => Some(Token::new(TokenKind::KeywordConst, index)),
+            "volatile" => Some(Token::new(TokenKind::KeywordVolatile, index)),
+            "static" => Some(Token::new(TokenKind::KeywordStatic, index)),
+            "extern" => Some(Token::new(TokenKind::KeywordExtern, index)),
+            "_Thread_local" | "__thread" => Some(Token::new(TokenKind::KeywordThreadLocal, index)),
+            "struct" => Some(Token::new(TokenKind::KeywordStruct, index)),
+            "_Alignas" => Some(Token::new(TokenKind::KeywordAlignas, index)),
+            "_Alignof" => Some(Token::new(TokenKind::KeywordAlignof, index)),
+            "sizeof" => Some(Token::new(TokenKind::KeywordSizeof, index)),
+            "_Generic" => Some(Token::new(TokenKind::KeywordGeneric, index)),
             _ => Some(Token::new(TokenKind::Identifier(value), index)),
         }
     }
@@ -118,8 +249,11 @@ impl<'a> Tokenizer<'a> {
     pub fn consume(&mut self) -> Option<Token> {
         self.skip_whitespaces();
 
-        None.or_else(|| self.consume_2_chars())
+        None.or_else(|| self.consume_3_chars())
+            .or_else(|| self.consume_2_chars())
             .or_else(|| self.consume_char())
+            .or_else(|| self.consume_string())
+            .or_else(|| self.consume_floating())
             .or_else(|| self.consume_integer())
             .or_else(|| self.consume_identifier_and_keywords())
     }
@@ -134,6 +268,7 @@ impl<'a> IntoIterator for Tokenizer<'a> {
     }
 }
 
+#[derive(Clone)]
 pub struct TokenizerIterator<'a> {
     tokenizer: Tokenizer<'a>,
 }
@@ -0,0 +1,553 @@
+// bit-fields take width bits from bit_offset in the storage unit of their type at the member offset
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BitField {
+    pub bit_offset: usize,
+    pub width: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Member {
+    pub name: String,
+    pub ty: Type,
+    pub offset: usize,
+    pub bit_field: Option<BitField>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Type {
+    // holds only 0 and 1, to which any other value converts by comparing with 0
+    Bool,
+    // plain char is signed but a type of its own, as are long and long long of the same size
+    Char,
+    SignedChar,
+    UnsignedChar,
+    Short,
+    UnsignedShort,
+    Int,
+    UnsignedInt,
+    Long,
+    UnsignedLong,
+    LongLong,
+    UnsignedLongLong,
+    Float,
+    Double,
+    Void,
+    // __va_list_tag of the System V ABI, of which va_list is an array of one
+    VaList,
+    Pointer(Box<Type>),
+    // the length is unknown until an initializer completes the type
+    Array {
+        element: Box<Type>,
+        length: Option<usize>,
+    },
+    // arrays whose lengths are only known at run time, which only locals can have
+    // their sizes in bytes are computed where they are declared into the locals at size_offset
+    VariableArray {
+        element: Box<Type>,
+        size_offset: usize,
+    },
+    // the last member may be an array of unknown length, which takes no space
+    Struct {
+        members: Vec<Member>,
+        size: usize,
+        align: usize,
+    },
+    // structs declared without members, which are completed by looking up their tags again
+    // once they are defined
+    IncompleteStruct {
+        tag: String,
+    },
+    // functions declared with empty parentheses have no prototype and take any arguments
+    Function {
+        return_ty: Box<Type>,
+        parameters: Vec<Type>,
+        is_variadic: bool,
+        has_prototype: bool,
+    },
+    // qualifiers only matter to objects, so values never have qualified types
+    // every access to a volatile object is emitted as written, as nothing is optimized away
+    // alignments given by _Alignas are kept here too, as they only matter to objects
+    Qualified {
+        ty: Box<Type>,
+        is_const: bool,
+        is_volatile: bool,
+        align: Option<usize>,
+    },
+}
+
+impl Type {
+    // alignments given by _Alignas only matter to objects, not to what pointers point to
+    pub fn pointer_to(ty: Type) -> Self {
+        Self::Pointer(ty.unaligned().into())
+    }
+
+    pub fn array_of(element: Type, length: Option<usize>) -> Self {
+        Self::Array {
+            element: element.into(),
+            length,
+        }
+    }
+
+    // members are laid out as by GCC on x86-64, where bit-fields are packed in order
+    // unless they would cross a boundary of a storage unit of their type
+    // unnamed bit-fields only take up space, and those of width 0 move to the next unit
+    pub fn struct_of(declarations: Vec<(Option<String>, Type, Option<usize>)>) -> Self {
+        let mut bit_offset: usize = 0;
+        let mut align = 1;
+        let mut members = Vec::<Member>::new();
+        for (name, ty, width) in declarations {
+            let Some(width) = width else {
+                let offset = bit_offset.div_ceil(8).next_multiple_of(ty.align());
+                align = align.max(ty.align());
+                bit_offset = 8 * (offset + ty.size());
+                members.push(Member {
+                    name: name.unwrap_or_default(),
+                    ty,
+                    offset,
+                    bit_field: None,
+                });
+                continue;
+            };
+
+            let unit_bits = 8 * ty.size();
+            if width == 0 || bit_offset / unit_bits != (bit_offset + width - 1) / unit_bits {
+                bit_offset = bit_offset.next_multiple_of(unit_bits);
+            }
+            if let Some(name) = name {
+                align = align.max(ty.align());
+                let offset = bit_offset / unit_bits * ty.size();
+                members.push(Member {
+                    name,
+                    bit_field: Some(BitField {
+                        bit_offset: bit_offset - 8 * offset,
+                        width,
+                    }),
+                    ty,
+                    offset,
+                });
+            }
+            bit_offset += width;
+        }
+
+        Self::Struct {
+            members,
+            size: bit_offset.div_ceil(8).next_multiple_of(align),
+            align,
+        }
+    }
+
+    // qualifiers of arrays apply to their elements
+    pub fn qualified(ty: Type, is_const: bool, is_volatile: bool) -> Self {
+        match ty {
+            _ if !is_const && !is_volatile => ty,
+            Self::Array { element, length } => {
+                Self::array_of(Self::qualified(*element, is_const, is_volatile), length)
+            }
+            Self::Qualified {
+                ty,
+                is_const: was_const,
+                is_volatile: was_volatile,
+                align,
+            } => Self::Qualified {
+                ty,
+                is_const: is_const || was_const,
+                is_volatile: is_volatile || was_volatile,
+                align,
+            },
+            ty => Self::Qualified {
+                ty: ty.into(),
+                is_const,
+                is_volatile,
+                align: None,
+            },
+        }
+    }
+
+    // alignments of arrays apply to their elements as well, and never lower the natural ones
+    pub fn aligned(ty: Type, align: usize) -> Self {
+        match ty {
+            _ if align <= ty.align() => ty,
+            Self::Array { element, length } => {
+                Self::array_of(Self::aligned(*element, align), length)
+            }
+            Self::Qualified {
+                ty,
+                is_const,
+                is_volatile,
+                ..
+            } => Self::Qualified {
+                ty,
+                is_const,
+                is_volatile,
+                align: Some(align),
+            },
+            ty => Self::Qualified {
+                ty: ty.into(),
+                is_const: false,
+                is_volatile: false,
+                align: Some(align),
+            },
+        }
+    }
+
+    // qualifiers of parameters and return values do not make up the type of a function
+    pub fn function_returning(return_ty: Type, parameters: Vec<Type>, is_variadic: bool) -> Self {
+        Self::Function {
+            return_ty: return_ty.unqualified().to_owned().into(),
+            parameters: parameters
+                .iter()
+                .map(|parameter| parameter.unqualified().to_owned())
+                .collect(),
+            is_variadic,
+            has_prototype: true,
+        }
+    }
+
+    pub fn function_without_prototype(return_ty: Type) -> Self {
+        Self::Function {
+            return_ty: return_ty.unqualified().to_owned().into(),
+            parameters: vec![],
+            is_variadic: false,
+            has_prototype: false,
+        }
+    }
+
+    // separates the alignment given by _Alignas from the rest of the type
+    pub fn split_align(self) -> (Type, Option<usize>) {
+        match self {
+            Self::Qualified {
+                ty,
+                is_const,
+                is_volatile,
+                align,
+            } => (Self::qualified(*ty, is_const, is_volatile), align),
+            ty => (ty, None),
+        }
+    }
+
+    pub fn unaligned(self) -> Self {
+        match self {
+            Self::Array { element, length } => Self::array_of(element.unaligned(), length),
+            ty => ty.split_align().0,
+        }
+    }
+
+    // declarations of the same object may differ in alignment, the strictest of which applies
+    pub fn redeclared(&self, ty: &Type) -> Option<Type> {
+        (self.to_owned().unaligned() == ty.to_owned().unaligned())
+            .then(|| Self::aligned(ty.to_owned(), self.align()))
+    }
+
+    pub fn unqualified(&self) -> &Type {
+        match self {
+            Self::Qualified { ty, .. } => ty,
+            _ => self,
+        }
+    }
+
+    // objects can only be defined with complete types, whose sizes are known
+    pub fn is_complete(&self) -> bool {
+        match self.unqualified() {
+            Self::Void | Self::IncompleteStruct { .. } | Self::Array { length: None, .. } => false,
+            Self::Array { element, .. } => element.is_complete(),
+            _ => true,
+        }
+    }
+
+    pub fn is_const(&self) -> bool {
+        match self {
+            Self::Qualified { is_const, .. } => *is_const,
+            Self::Array { element, .. } | Self::VariableArray { element, .. } => element.is_const(),
+            _ => false,
+        }
+    }
+
+    pub fn is_volatile(&self) -> bool {
+        match self {
+            Self::Qualified { is_volatile, .. } => *is_volatile,
+            Self::Array { element, .. } | Self::VariableArray { element, .. } => {
+                element.is_volatile()
+            }
+            _ => false,
+        }
+    }
+
+    // members of members are looked into as well, as are elements of arrays
+    pub fn has_const_member(&self) -> bool {
+        match self.unqualified() {
+            Self::Struct { members, .. } => members
+                .iter()
+                .any(|member| member.ty.is_const() || member.ty.has_const_member()),
+            Self::Array { element, .. } => element.has_const_member(),
+            _ => false,
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        match self {
+            Self::Bool | Self::Char | Self::SignedChar | Self::UnsignedChar => 1,
+            Self::Short | Self::UnsignedShort => 2,
+            Self::Int | Self::UnsignedInt => 4,
+            Self::Long | Self::UnsignedLong | Self::LongLong | Self::UnsignedLongLong => 8,
+            Self::Float => 4,
+            Self::Double => 8,
+            Self::VaList => 24,
+            Self::Pointer(_) => 8,
+            Self::Array { element, length } => element.size() * length.unwrap_or(0),
+            // only known at run time
+            Self::VariableArray { .. } => 0,
+            Self::Struct { size, .. } => *size,
+            Self::IncompleteStruct { .. } => 0,
+            Self::Qualified { ty, .. } => ty.size(),
+            // as in GNU C, which allows arithmetic on pointers to them
+            Self::Void | Self::Function { .. } => 1,
+        }
+    }
+
+    pub fn align(&self) -> usize {
+        match self {
+            Self::VaList => 8,
+            Self::Array { element, .. } | Self::VariableArray { element, .. } => element.align(),
+            Self::Struct { align, .. } => *align,
+            Self::IncompleteStruct { .. } => 1,
+            Self::Qualified { ty, align, .. } => align.unwrap_or(1).max(ty.align()),
+            _ => self.size(),
+        }
+    }
+
+    pub fn is_integer(&self) -> bool {
+        matches!(
+            self.unqualified(),
+            Self::Bool
+                | Self::Char
+                | Self::SignedChar
+                | Self::UnsignedChar
+                | Self::Short
+                | Self::UnsignedShort
+                | Self::Int
+                | Self::UnsignedInt
+                | Self::Long
+                | Self::UnsignedLong
+                | Self::LongLong
+                | Self::UnsignedLongLong
+        )
+    }
+
+    pub fn is_arithmetic(&self) -> bool {
+        self.is_integer() || self.is_floating()
+    }
+
+    pub fn is_floating(&self) -> bool {
+        matches!(self.unqualified(), Self::Float | Self::Double)
+    }
+
+    pub fn is_pointer(&self) -> bool {
+        matches!(self.unqualified(), Self::Pointer(_))
+    }
+
+    pub fn is_scalar(&self) -> bool {
+        self.is_arithmetic() || self.is_pointer()
+    }
+
+    pub fn is_aggregate(&self) -> bool {
+        matches!(
+            self.unqualified(),
+            Self::Array { .. } | Self::VariableArray { .. } | Self::Struct { .. }
+        )
+    }
+
+    // types derived from variable length arrays have sizes only known at run time
+    pub fn is_variably_modified(&self) -> bool {
+        match self.unqualified() {
+            Self::VariableArray { .. } => true,
+            Self::Pointer(ty) | Self::Array { element: ty, .. } => ty.is_variably_modified(),
+            _ => false,
+        }
+    }
+
+    pub fn is_function(&self) -> bool {
+        matches!(self.unqualified(), Self::Function { .. })
+    }
+
+    pub fn is_signed(&self) -> bool {
+        matches!(
+            self.unqualified(),
+            Self::Char | Self::SignedChar | Self::Short | Self::Int | Self::Long | Self::LongLong
+        )
+    }
+
+    // integer promotion
+    // every integer type narrower than int fits in int
+    pub fn promoted(&self) -> Type {
+        match self {
+            Self::Bool
+            | Self::Char
+            | Self::SignedChar
+            | Self::UnsignedChar
+            | Self::Short
+            | Self::UnsignedShort => Self::Int,
+            _ => self.to_owned(),
+        }
+    }
+
+    // usual arithmetic conversions of two arithmetic operands
+    pub fn common_type(lhs: &Type, rhs: &Type) -> Type {
+        if *lhs == Self::Double || *rhs == Self::Double {
+            return Self::Double;
+        }
+        if *lhs == Self::Float || *rhs == Self::Float {
+            return Self::Float;
+        }
+
+        let (lhs, rhs) = (lhs.promoted(), rhs.promoted());
+        if lhs == rhs {
+            return lhs;
+        }
+
+        if lhs.is_signed() == rhs.is_signed() {
+            return if lhs.rank() >= rhs.rank() { lhs } else { rhs };
+        }
+
+        let (signed, unsigned) = if lhs.is_signed() {
+            (lhs, rhs)
+        } else {
+            (rhs, lhs)
+        };
+        // a wider signed type can represent all the values of the unsigned one on LP64
+        // while long long cannot represent those of unsigned long, which ranks lower
+        if unsigned.rank() >= signed.rank() {
+            unsigned
+        } else if signed.size() > unsigned.size() {
+            signed
+        } else {
+            Self::UnsignedLongLong
+        }
+    }
+
+    // integer conversion rank, which orders types of the same size as well
+    fn rank(&self) -> usize {
+        match self {
+            Self::Bool => 0,
+            Self::Char | Self::SignedChar | Self::UnsignedChar => 1,
+            Self::Short | Self::UnsignedShort => 2,
+            Self::Int | Self::UnsignedInt => 3,
+            Self::Long | Self::UnsignedLong => 4,
+            _ => 5,
+        }
+    }
+
+    // unsigned integers of 64 bits, which cvtsi2sd and cvttsd2si treat as signed
+    pub fn is_unsigned_long(&self) -> bool {
+        matches!(
+            self.unqualified(),
+            Self::UnsignedLong | Self::UnsignedLongLong
+        )
+    }
+
+    // truncates an integer constant the same way as a conversion at run time
+    pub fn convert_constant(&self, value: i64) -> i64 {
+        match self {
+            Self::Bool => (value != 0).into(),
+            Self::Char | Self::SignedChar => value as i8 as i64,
+            Self::UnsignedChar => value as u8 as i64,
+            Self::Short => value as i16 as i64,
+            Self::UnsignedShort => value as u16 as i64,
+            Self::Int => value as i32 as i64,
+            Self::UnsignedInt => value as u32 as i64,
+            _ => value,
+        }
+    }
+
+    // the pointee keeps its qualifiers, which tell whether it can be assigned to
+    pub fn pointee(&self) -> Option<&Type> {
+        match self.unqualified() {
+            Self::Pointer(ty) => Some(ty),
+            _ => None,
+        }
+    }
+
+    // types declared for the same entity must be compatible
+    // incomplete structs are compatible with the structs they may be completed to
+    // and arrays of unknown length with arrays of any length
+    pub fn is_compatible(&self, other: &Type) -> bool {
+        match (self, other) {
+            (Self::Pointer(lhs), Self::Pointer(rhs)) => lhs.is_compatible(rhs),
+            (
+                Self::Array {
+                    element: lhs,
+                    length: lhs_length,
+                },
+                Self::Array {
+                    element: rhs,
+                    length: rhs_length,
+                },
+            ) => {
+                lhs.is_compatible(rhs)
+                    && (lhs_length.is_none() || rhs_length.is_none() || lhs_length == rhs_length)
+            }
+            (Self::IncompleteStruct { .. }, Self::Struct { .. })
+            | (Self::Struct { .. }, Self::IncompleteStruct { .. }) => true,
+            (
+                Self::Function {
+                    return_ty: lhs_return_ty,
+                    parameters: lhs_parameters,
+                    is_variadic: lhs_is_variadic,
+                    has_prototype: lhs_has_prototype,
+                },
+                Self::Function {
+                    return_ty: rhs_return_ty,
+                    parameters: rhs_parameters,
+                    is_variadic: rhs_is_variadic,
+                    has_prototype: rhs_has_prototype,
+                },
+            ) => {
+                lhs_return_ty.is_compatible(rhs_return_ty)
+                    && match (lhs_has_prototype, rhs_has_prototype) {
+                        (true, true) => {
+                            lhs_is_variadic == rhs_is_variadic
+                                && lhs_parameters.len() == rhs_parameters.len()
+                                && lhs_parameters
+                                    .iter()
+                                    .zip(rhs_parameters)
+                                    .all(|(lhs, rhs)| lhs.is_compatible(rhs))
+                        }
+                        // prototypes must not have parameters changed by default argument promotions
+                        (true, false) => {
+                            Self::is_promotion_stable(lhs_parameters, *lhs_is_variadic)
+                        }
+                        (false, true) => {
+                            Self::is_promotion_stable(rhs_parameters, *rhs_is_variadic)
+                        }
+                        (false, false) => true,
+                    }
+            }
+            (
+                Self::Qualified {
+                    ty: lhs,
+                    is_const: lhs_is_const,
+                    is_volatile: lhs_is_volatile,
+                    align: lhs_align,
+                },
+                Self::Qualified {
+                    ty: rhs,
+                    is_const: rhs_is_const,
+                    is_volatile: rhs_is_volatile,
+                    align: rhs_align,
+                },
+            ) => {
+                lhs_is_const == rhs_is_const
+                    && lhs_is_volatile == rhs_is_volatile
+                    && lhs_align == rhs_align
+                    && lhs.is_compatible(rhs)
+            }
+            (lhs, rhs) => lhs == rhs,
+        }
+    }
+
+    fn is_promotion_stable(parameters: &[Type], is_variadic: bool) -> bool {
+        !is_variadic
+            && parameters
+                .iter()
+                .all(|parameter| *parameter != Type::Float && parameter.promoted() == *parameter)
+    }
+}
@@ -1,14 +1,30 @@
 #!/bin/bash
 asset_print_foo="tmp_print_foo.o"
 asset_print_sum="tmp_print_sum.o"
-assets="$asset_print_foo $asset_print_sum"
+asset_print_doubles="tmp_print_doubles.o"
+asset_add_doubles="tmp_add_doubles.o"
+asset_apply="tmp_apply.o"
+asset_format_string="tmp_format_string.o"
+asset_sum_va_list="tmp_sum_va_list.o"
+asset_weighted_sum="tmp_weighted_sum.o"
+asset_weighted_doubles="tmp_weighted_doubles.o"
+asset_call_many="tmp_call_many.o"
+asset_helper="tmp_helper.o"
+asset_counter="tmp_counter.o"
+asset_mixed="tmp_mixed.o"
+asset_primes="tmp_primes.o"
+asset_flags="tmp_flags.o"
+asset_shapes="tmp_shapes.o"
+asset_bit_fields="tmp_bit_fields.o"
+asset_threads="tmp_threads.o"
+assets="$asset_print_foo $asset_print_sum $asset_print_doubles $asset_add_doubles $asset_apply $asset_format_string $asset_sum_va_list $asset_weighted_sum $asset_weighted_doubles $asset_call_many $asset_helper $asset_counter $asset_mixed $asset_primes $asset_flags $asset_shapes $asset_bit_fields $asset_threads"
 
 assert() {
   expected="$1"
   input="$2"
 
   echo "$input" | ./target/debug/r9cc > tmp.s
-  cc -o tmp tmp.s
+  cc -o tmp tmp.s $assets -lm -pthread
   ./tmp
   actual="$?"
 
@@ -28,7 +44,7 @@ assert_stdout() {
   input="$2"
 
   echo "$input" | ./target/debug/r9cc > tmp.s
-  cc -o tmp tmp.s $assets
+  cc -o tmp tmp.s $assets -lm -pthread
   actual="$(./tmp)"
 
   if [ "$actual" = "$expected" ]; then
@@ -45,6 +61,25 @@ assert_stdout() {
 cargo build
 cc -o "$asset_print_foo" -c "test_assets/print_foo.c"
 cc -o "$asset_print_sum" -c "test_assets/print_sum.c"
+cc -o "$asset_print_doubles" -c "test_assets/print_doubles.c"
+cc -o "$asset_add_doubles" -c "test_assets/add_doubles.c"
+cc -o "$asset_apply" -c "test_assets/apply.c"
+cc -o "$asset_format_string" -c "test_assets/format_string.c"
+cc -o "$asset_sum_va_list" -c "test_assets/sum_va_list.c"
+cc -o "$asset_weighted_sum" -c "test_assets/weighted_sum.c"
+cc -o "$asset_weighted_doubles" -c "test_assets/weighted_doubles.c"
+cc -o "$asset_call_many" -c "test_assets/call_many.c"
+cc -o "$asset_helper" -c "test_assets/helper.c"
+cc -o "$asset_counter" -c "test_assets/counter.c"
+cc -o "$asset_mixed" -c "test_assets/mixed.c"
+cc -o "$asset_primes" -c "test_assets/primes.c"
+cc -o "$asset_flags" -c "test_assets/flags.c"
+cc -o "$asset_shapes" -c "test_assets/shapes.c"
+cc -o "$asset_bit_fields" -c "test_assets/bit_fields.c"
+cc -o "$asset_threads" -c "test_assets/threads.c"
+struct_mixed="struct Mixed { char c; int i; double d; short s; char *p; };"
+struct_bit_fields="struct Header { unsigned version : 4; unsigned length : 4; unsigned type : 8; unsigned short total; }; struct Bits { char a; int b : 5; int c : 20; unsigned d : 10; long e : 40; short f : 3; _Bool g : 1; int : 0; char h; unsigned : 7; unsigned i : 30; };"
+struct_shapes="struct C1 { char c; }; struct II { int a; int b; }; struct LL { long a; long b; }; struct DD { double a; double b; }; struct FFF { float a; float b; float c; }; struct ID { int i; double d; }; struct C3 { char c[3]; }; struct L3 { long a[3]; }; struct Mix { char c; short s; int i; long l; }; struct FI { float f; int i; double d; };"
 
 assert 10 " 10 ; "
 assert 41 " 12 + 34 -  5 ; "
@@ -79,8 +114,269 @@ assert 55 "sum = 0; i = 0; while(i <= 9) sum = sum + (i = i + 1); sum;"
 assert 10 "for(i = 0; i < 10; i = i + 1) i; i;"
 assert 55 "sum = 0; for(i = 1; i <= 10; i = i + 1) sum = sum + i; sum;"
 assert 55 "sum = 0; i = 1; while(i <= 10) { sum = sum + i; i = i + 1; } sum;"
+assert 3 "i = 0; while(1) { i = i + 1; if(i == 3) break; } i;"
+assert 20 "x = 0; switch(2) { case 1: x = 10; break; case 2: x = 20; break; default: x = 30; } x;"
+assert 30 "x = 0; switch(5) { case 1: x = 10; break; case 2: x = 20; break; default: x = 30; } x;"
+assert 0 "x = 0; switch(5) { case 1: x = 10; break; case 2: x = 20; break; } x;"
+assert 6 "x = 0; switch(1) { case 1: x = x + 1; case 2: x = x + 2; case 3: x = x + 3; break; case 4: x = x + 4; } x;"
+assert 7 "x = 0; switch(0 - 1) { default: x = 3; case 0 - 1: x = x + 7; } x;"
+assert 3 "long x = 0; switch(4294967297) { case 1: x = 1; break; case 4294967297: x = 3; } x;"
+assert 1 "unsigned x = 4000000000; long y = 4294967297; (x / 1000000000 == 4) * (y - 4294967296 == 1) * (sizeof(2147483648) == 8) * (sizeof(2147483647) == 4);"
+assert 4 "x = 0; for(i = 0; i < 5; i = i + 1) switch(i) { case 1: case 3: x = x + i; break; default: break; } x;"
+assert 15 "unsigned u = 0 - 1; unsigned char uc = 255; char c = 0 - 1; long l = 0 - 2; x = 0; switch(u) { case 0 - 1: x = 1; } switch(uc) { case 0 - 1: break; case 255: x = x + 2; } switch(c) { case 255: break; case 0 - 1: x = x + 4; } switch(l) { case 0 - 2: x = x + 8; } x;"
+assert 5 "i = 0; loop: i = i + 1; if(i < 5) goto loop; i;"
+assert 1 "x = 1; goto skip; x = 2; skip: x;"
+assert 3 "i = 0; while(1) { while(1) { i = i + 1; if(i == 3) goto out; } } out: i;"
+assert 1 "x = 1; { int x = 2; } x;"
+assert 2 "int x = 1; { int x = 2; { x = 3; } return x - 1; }"
+assert 3 "int a = 1, b = 2; { int a = 10; b = b + 1; } a * b;"
+assert 11 "x = 0; { int a = 1; x = x + a; } { int b = 10; x = x + b; } x;"
+assert 10 "int i = 10; for(int i = 0; i < 5; i = i + 1) { int i = 7; } i;"
+assert 15 "s = 0; for(int i = 0; i < 5; i = i + 1) { int j = i + 1; s = s + j; } s;"
+assert 3 "{ y = 3; } y;"
+assert 44 "(char)300;"
+assert 44 "char c = 300; c;"
+assert 255 "unsigned char c = 0 - 1; c;"
+assert 1 "char c = 0 - 1; c < 0;"
+assert 0 "unsigned char c = 0 - 1; c < 0;"
+assert 1 "(short)65537;"
+assert 1 "long x = (long)65536 * 65536; x / 65536 / 65536;"
+assert 0 "int x = (long)65536 * 65536; x;"
+assert 1 "long x = (long)(unsigned int)(0 - 1); x == 65536 * (long)65536 - 1;"
+assert 3 "int x = 3; int *p = &x; *p;"
+assert 5 "int x = 3; int *p = &x; *p = 5; x;"
+assert 7 "int x = 7; long a = (long)&x; *(int *)a;"
+assert 9 "int x = 9; int *p = &x; int **pp = &p; **pp;"
+assert 2 "{ long a = 1; long b = 2; long *p = &a; *(p - 1); }"
+assert 3 "long a; long *p = &a; long *q = p + 3; q - p;"
+assert 1 "unsigned int u = 1; int i = 0 - 1; i > u;"
+assert 0 "long l = 1; int i = 0 - 1; i > l;"
+assert 1 "unsigned char c = 255; int i = 0 - 1; c > i;"
+assert 1 "unsigned int u = 0 - 1; long l = u; l > 0;"
+assert 1 "unsigned long u = 0 - 1; u > 0;"
+assert 1 "unsigned int u = 0; u - 1 > 0;"
+assert 0 "int i = 0 - 7; unsigned int u = 2; (i / u) < 10;"
+assert 255 "unsigned char c = 255; (c + c) / 2;"
+assert 1 "char c = 0 - 128; -c == 128;"
+assert 1 "char c; (c = 300) == 44;"
+assert 4 "(int)(1.5 + 2.5);"
+assert 15 "double d = 3; (int)(d / 2 * 10);"
+assert 8 "(int)-2.7 + 10;"
+assert 1 "float f = 0.1f; double d = f; d != 0.1;"
+assert 1 "float f = 0.5f; double d = f; d == 0.5;"
+assert 1 "1.5 < 2.5;"
+assert 0 "2.5 < 1.5;"
+assert 1 "2.5 <= 2.5;"
+assert 1 "1.0 == 1;"
+assert 0 "1.0 != 1;"
+assert 1 "1e3 == 1000;"
+assert 25 "float f = .5f; (int)(f * 50);"
+assert 7 "int i = 7; double d = i; float f = d; (int)f;"
+assert 3 "char c = 3.9; c;"
+assert 10 "unsigned long u = (unsigned long)1e19; (int)(u / 1000000000 / 1000000000);"
+assert 1 "double d = (unsigned long)0 - 1; d > 1.8e19;"
+assert 2 "double d = 0.5; if(d) 2; else 3;"
+assert 3 "double d = 0.0 * -1; if(d) 2; else 3;"
+assert 4 "double sqrt(double x); (int)sqrt(16.0);"
+assert 1 "double pow(double, double); pow(2.0, 10.0) == 1024;"
+assert 7 "int add(int a, int b) { return a + b; } add(3, 4);"
+assert 55 "int fib(int n) { if (n < 2) return n; return fib(n - 1) + fib(n - 2); } fib(10);"
+assert 42 "int main() { return 42; }"
+assert 9 "double half(double x) { return x / 2; } (int)(half(9.0) * 2);"
+assert 42 "int twice(int x) { return x * 2; } int (*fp)(int) = twice; fp(21);"
+assert 10 "int twice(int x) { return x * 2; } int (*fp)(int) = &twice; (*fp)(4) + (**fp)(1);"
+assert 21 "int one() { return 1; } int two() { return 2; } int (*pick(int i))() { if (i) return two; return one; } pick(1)() * 10 + pick(0)();"
+assert 13 "int offset() { return 1; } int rdi(int x) { return x * 2; } int xor(int (*f)(int)) { return f(3); } int (*p)(int) = rdi; offset() + rdi(2) + p(1) + xor(rdi);"
+assert 7 "int sub(int a, int b) { return a - b; } apply(sub, 10, 3);"
+assert 123 "int cmp(long *a, long *b) { return *a - *b; } long *calloc(long n, long size); long *p = calloc(3, 8); *p = 3; *(p + 1) = 1; *(p + 2) = 2; qsort(p, 3, 8, cmp); *p * 100 + *(p + 1) * 10 + *(p + 2);"
+assert 10 "int sum(int n, ...) { va_list ap; va_start(ap, n); int total = 0; int i; for (i = 0; i < n; i = i + 1) total = total + va_arg(ap, int); va_end(ap); return total; } sum(4, 1, 2, 3, 4);"
+assert 7 "double sum(int n, ...) { va_list ap; va_start(ap, n); double total = 0; for (; n; n = n - 1) total = total + va_arg(ap, double); return total; } (int)sum(3, 1.5, 2.5, 3.0);"
+assert 60 "int sum(int n, ...) { va_list ap; va_start(ap, n); int total = sum_va_list(n, ap); va_end(ap); return total; } sum(3, 10, 20, 30);"
+assert 150 "int first_and_rest(int n, ...) { va_list ap; va_start(ap, n); int first = va_arg(ap, int); return first * 10 + sum_va_list(n - 1, ap); } first_and_rest(3, 10, 20, 30);"
+assert 5 "int g; void set(int x) { if (x < 0) return; g = x; } set(5); set(0 - 1); g;"
+assert 5 "int x = 5; void *p = &x; const void *q = 0; *p; *q; *(int *)&*p;"
+assert 42 "int pick(double d, ...) { va_list ap; va_start(ap, d); va_arg(ap, double); return va_arg(ap, int) + (int)d; } pick(2.0, 1.0, 40);"
+assert 165 "weighted_sum(9, 8, 7, 6, 5, 4, 3, 2, 1);"
+assert 101 "double weighted_doubles(double a, double b, double c, double d, double e, double f, double g, double h, double i, double j); (int)(weighted_doubles(1.5, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 1.0, 0.5) * 2);"
+assert 204 "long weighted(long a, long b, long c, long d, long e, long f, long g, long h) { return a + 2 * b + 3 * c + 4 * d + 5 * e + 6 * f + 7 * g + 8 * h; } call_many(weighted);"
+assert 120 "int last(int a, double b, int c, double d, int e, double f, int g, double h, int i, double j, int k, double l, int m, double n, int o, double p, int q, double r) { return (int)(r * 100) + q; } last(1, 1.0, 2, 2.0, 3, 3.0, 4, 4.0, 5, 5.0, 6, 6.0, 7, 7.0, 8, 8.0, 9, 1.11);"
+assert 36 "int sum(int n, ...) { va_list ap; va_start(ap, n); int total = 0; for (; n; n = n - 1) total = total + va_arg(ap, int); return total; } sum(8, 1, 2, 3, 4, 5, 6, 7, 8);"
+assert 25 "int sum(int a, int b, int c, int d, int e, int f, int g, ...) { va_list ap; va_start(ap, g); return g + va_arg(ap, int) + va_arg(ap, int); } sum(1, 2, 3, 4, 5, 6, 7, 8, 10);"
+assert 42 "int g; int set() { g = 42; return 0; } set(); g;"
+assert 3 "double g = 1.5; (int)(g * 2);"
+assert 6 "int a = 2; int b = a * 3; b;"
+assert 42 "int g = 40; int main() { return g + 2; }"
+assert 3 "int count() { static int n; n = n + 1; return n; } count(); count(); count();"
+assert 13 "int count() { static int n = 10; n = n + 1; return n; } int other() { static int n = 100; return n; } count(); count(); count() + other() - 100;"
+assert 42 "static int helper() { return 40; } helper() + 2;"
+assert 5 "static int counter = 5; counter;"
+assert 11 "extern int counter; counter + 1;"
+assert 20 "int bump() { extern int counter; return counter = counter + 5; } bump(); bump();"
+assert 37 "int byte = 2; int offset = 3; int rip; int *p = &offset; int read() { return rip; } rip = 5; byte * 10 + *p * read() + 2;"
+assert 9 "static int byte = 4; static int *rip = &byte; int get() { extern int byte; return byte + *rip; } get() + 1;"
+assert 6 "int a[] = {1, 2, 3}; a[0] + a[1] + a[2];"
+assert 15 "int a[3] = {1, 2, 3}; (long)(&a + 1) - (long)&a + *(*(&a + 1) - 1);"
+assert 0 "int dirty() { int a[4] = {1, 2, 3, 4}; return a[3]; } int clean() { int a[4] = {1}; return a[1] + a[2] + a[3]; } dirty(); clean();"
+assert 12 "int a[10] = { [5] = 2, 7, [1] = 3 }; a[1] + a[5] + a[6] + a[0] + a[9];"
+assert 12 "struct P { int x; int y; }; struct P p = { .y = 2, .x = 1 }; p.x * 10 + p.y;"
+assert 35 "struct A { int a; struct { int x; int y; } b; }; struct A v = { 3, .b.y = 5 }; v.a * 10 + v.b.y + v.b.x;"
+assert 123 "int m[2][3] = { 1, 2, 3, 4, 5, 6 }; int n[2][3] = { {1}, {4, 5} }; m[1][2] * 10 + m[0][1] * 10 + n[1][1] * 10 + n[0][1] - 7;"
+assert 6 "char s[] = \"hello\"; char t[8] = {\"hi\"}; (long)(&s + 1) - (long)&s + s[1] - 101 + t[7];"
+assert 111 "int f() { char s[] = \"hello\"; return s[4]; } f();"
+assert 7 "int x = 3; int y[2] = {4, 5}; int *p = &x; int *q = y + 1; *p + *q - 1;"
+assert 9 "int one() { return 1; } int two() { return 2; } int (*table[])() = { one, two, two }; struct { int (*f)(); int n; } entry = { two, 7 }; table[0]() + table[1]() + entry.f() + entry.n - 3;"
+assert 5 "struct P { int x; int y; }; struct P ps[2] = { {1, 2}, [1].y = 4 }; struct P *p = ps; p->y + (p + 1)->y - 1;"
+assert 3 "struct N { char c; }; struct L { char c; struct N *next; }; struct N last = { 2 }; struct L first = { 1, &last }; first.c + first.next->c;"
+assert 15 "struct N { struct N *p; int v; }; struct N n = {&n, 5}; (n.p == &n) * 10 + n.p->v;"
+assert 15 "int f() { struct N { struct N *p; int v; } n = {&n, 5}; return (n.p == &n) * 10 + n.p->v; } f();"
+assert 1 "int f() { static void *p = &p; return p == &p; } f();"
+assert 116 "$struct_mixed struct Mixed m = {1, 2, 3.5, 4, \"five\"}; sum_mixed(&m);"
+assert 112 "$struct_mixed extern struct Mixed mixed; (long)(mixed.d * 2) + mixed.p[1];"
+assert 33 "extern int primes[5]; primes[4] * primes[1];"
+assert 2 "struct P { int x; int y; }; (struct P){1, 2}.y;"
+assert 7 "struct P { int x; int y; }; struct P *p = &(struct P){3, 4}; p->x + p->y;"
+assert 12 "int f() { int *p = (int[]){4, 5, 6}; return p[1] + (int){7}; } f();"
+assert 4 "int f() { int *p = &(int){3}; *p = *p + 1; return *p; } f();"
+assert 3 "int f(int v) { int s = 0; int i; for (i = 0; i < 3; i = i + 1) s = s + ((int[]){v, i})[1]; return s; } f(9);"
+assert 6 "int g(int x) { return x; } int f() { return ((int[]){1, g(5)})[1] + g(1); } f();"
+assert 1 "_Bool b = 256; b;"
+assert 12 "_Bool b = 0.5; int f() { _Bool c = 0.0; _Bool d = -0.0 + 1; return c * 10 + d + 10; } b + f();"
+assert 1 "int f(double x) { _Bool b = x; return b; } f(0.1) + f(0.0) * 2;"
+assert 1 "int f() { _Bool b = 2; b = b + 1; return b; } f();"
+assert 1 "int f(long x) { return (_Bool)x; } f((long)65536 * 65536);"
+assert 1 "_Bool a[3] = {5, 0, 3}; (long)(a + 1) - (long)a + a[1] + (1 < 2) - 1 + (_Bool)(2 == 3);"
+assert 2 "extern _Bool flags[4]; _Bool is_odd(int x); flags[0] + flags[1] + is_odd(7) + is_odd(8);"
+assert 2 "int count_flags(_Bool *f, int n); _Bool f[3] = {7, 0, -1}; count_flags(f, 3);"
+assert 98 "int f(const char *s) { s = s + 1; return *s; } f(\"ab\");"
+assert 7 "char buf[2]; char *const p = buf; *p = 7; buf[0];"
+assert 7 "struct P { const int x; int y; }; struct P p = {5, 6}; p.y = 2; p.x + p.y;"
+assert 9 "struct P { const int x; int y; }; struct O { struct P p; int z; }; struct O o = {{5, 6}, 1}; o.p.y = 2; o.z = 2; o.p.x + o.p.y + o.z;"
+assert 7 "volatile int v = 3; int const volatile w = 4; v = v + w; v;"
+assert 99 "int f() { static const char *const names[] = {\"ab\", \"cd\"}; return names[1][0]; } f();"
+assert 3 "unsigned const long volatile z = 3; (const int)z;"
+assert 104 "static char *greeting = \"hi\"; int f() { static char *s[] = { \"x\", \"hello\" }; return s[1][0]; } f() + greeting[1] - greeting[0] - 1;"
+assert 65 "$struct_shapes struct C1 make_c1(char c); make_c1(65).c;"
+assert 7 "$struct_shapes struct II make_ii(int a, int b); struct II s = make_ii(3, 4); s.a + s.b;"
+assert 34 "$struct_shapes struct LL make_ll(long a, long b); make_ll(30, 4).a + make_ll(30, 4).b;"
+assert 7 "$struct_shapes struct DD make_dd(double a, double b); struct DD s = make_dd(2.5, 4.5); (int)(s.a + s.b);"
+assert 9 "$struct_shapes struct FFF make_fff(double a, double b, double c); struct FFF s = make_fff(1.5, 3.0, 4.5); (int)(s.a + s.b + s.c);"
+assert 6 "$struct_shapes struct ID make_id(int i, double d); struct ID s = make_id(2, 4.5); (int)(s.i + s.d);"
+assert 18 "$struct_shapes struct C3 make_c3(char a, char b, char c); struct C3 s = make_c3(5, 6, 7); s.c[0] + s.c[1] + s.c[2];"
+assert 6 "$struct_shapes struct L3 make_l3(long a, long b, long c); struct L3 s = make_l3(1, 2, 3); s.a[0] + s.a[1] + s.a[2];"
+assert 10 "$struct_shapes struct Mix make_mix(char c, short s, int i, long l); struct Mix m = make_mix(1, 2, 3, 4); m.c + m.s + m.i + m.l;"
+assert 7 "$struct_shapes struct FI make_fi(double f, int i, double d); struct FI s = make_fi(1.5, 2, 3.5); (int)(s.f + s.i + s.d);"
+assert 9 "$struct_shapes struct C1 s = {9}; sum_c1(s);"
+assert 11 "$struct_shapes struct II s = {5, 6}; sum_ii(s);"
+assert 11 "$struct_shapes struct LL s = {5, 6}; sum_ll(s);"
+assert 11 "$struct_shapes double sum_dd(struct DD s); struct DD s = {5.5, 5.5}; (int)(sum_dd(s));"
+assert 12 "$struct_shapes double sum_fff(struct FFF s); struct FFF s = {3.5f, 4, 4.5f}; (int)(sum_fff(s));"
+assert 8 "$struct_shapes double sum_id(struct ID s); struct ID s = {3, 5.5}; (int)(sum_id(s));"
+assert 18 "$struct_shapes struct C3 s = {{5, 6, 7}}; sum_c3(s);"
+assert 60 "$struct_shapes struct L3 s = {{10, 20, 30}}; sum_l3(s);"
+assert 10 "$struct_shapes struct Mix m = {1, 2, 3, 4}; sum_mix(m);"
+assert 7 "$struct_shapes double sum_fi(struct FI s); struct FI s = {1.5f, 2, 3.5}; (int)(sum_fi(s));"
+assert 47 "$struct_shapes struct LL x = {2, 3}; struct II y = {4, 5}; struct L3 z = {{1, 2, 3}}; sum_spilled(1, 2, 3, 4, 5, x, y, z);"
+assert 44 "$struct_shapes double sum_doubles_spilled(double a, double b, double c, double d, double e, double f, double g, struct DD x, double h, struct ID y); struct DD x = {2, 3}; struct ID y = {4, 2.5}; (int)(sum_doubles_spilled(1.0, 2.0, 3.0, 4.0, 5.0, 6.0, 7.0, x, 0.0, y));"
+assert 34 "$struct_shapes struct LL make(long a, long b) { struct LL s = {a, b}; return s; } call_make_ll(make);"
+assert 123 "$struct_shapes struct L3 make(long a, long b, long c) { struct L3 s; s.a[0] = a; s.a[1] = b; s.a[2] = c; return s; } call_make_l3(make);"
+assert 6 "$struct_shapes struct FI make(float f, int i, double d) { struct FI s = {f, i, d}; return s; } double call_make_fi(struct FI (*make)(float, int, double)); (int)(call_make_fi(make) - 0.75);"
+assert 10 "$struct_shapes long sum(struct Mix m) { return m.c + m.s + m.i + m.l; } call_sum_mix(sum);"
+assert 18 "$struct_shapes long sum(struct C3 s) { return s.c[0] + s.c[1] + s.c[2]; } call_sum_c3(sum);"
+assert 47 "$struct_shapes long sum(long a, long b, long c, long d, long e, struct LL x, struct II y, struct L3 z) { return a + b + c + d + e + x.a * x.b + y.a * y.b + z.a[0] * z.a[1] * z.a[2]; } call_sum_spilled(sum);"
+assert 5 "$struct_shapes struct II p = {2, 3}; struct II q; q = p; p.a = 10; q.a + q.b;"
+assert 12 "$struct_shapes struct L3 p = {{2, 3, 7}}; struct L3 q = p; q.a[0] + q.a[1] + q.a[2];"
+assert 6 "$struct_shapes struct L3 id(struct L3 s) { return s; } struct L3 p = {{1, 2, 3}}; id(id(p)).a[2] * 2;"
+assert 3 "$struct_shapes struct II swap(struct II s) { struct II t = {s.b, s.a}; return t; } struct II p = {1, 2}; struct II q = swap(p); q.a * 10 + q.b - 18;"
+assert 5 "$struct_shapes struct LL make_ll(long a, long b); sum_ll(make_ll(2, 3));"
+assert 4 "$struct_shapes struct LL make_ll(long a, long b); struct LL x; struct LL y = {3, 1}; x = y = make_ll(1, 3); x.a + y.b;"
+assert 3 "int abs(int x); abs(-3.7);"
+assert 5 "float fabsf(float x); (int)(fabsf(-2.5f) * 2);"
+assert 4 "double sqrt(double x); (int)sqrt(16);"
+assert 1 "int f(char c) { return c; } f(257);"
+assert 6 "long g(long a, double b, int c); long g(long a, double b, int c) { return a + b + c; } g(1.9, 2, 3.5);"
+assert 12 "int h(); int h(int a, int b) { return a * b; } h(3, 4);"
+assert 6 "int apply(int (*f)(int, int), int a, int b); int add(int a, int b) { return a + b; } int (*p)(int, int) = add; apply(p, 2.5, 4);"
+assert 9 "$struct_shapes struct FFF make_fff(double a, double b, double c); double sum_fff(struct FFF s); (int)sum_fff(make_fff(1, 3, 5));"
+assert 1 "$struct_bit_fields int sizeof_bits(void); struct Bits b; (long)(&b + 1) - (long)&b == sizeof_bits();"
+assert 4 "$struct_bit_fields struct Header h; (long)(&h + 1) - (long)&h;"
+assert 15 "$struct_bit_fields extern struct Header header; header.version + header.length + header.type;"
+assert 1 "$struct_bit_fields extern struct Bits bits; (bits.b == -3) * (bits.c == 300000) * (bits.d == 1000) * (bits.e == -12345678901) * (bits.f == 3) * (bits.g == 1) * (bits.h == 104) * (bits.i == 123456789);"
+assert 15 "$struct_bit_fields extern struct Bits bits; int promoted_bits(struct Bits *b); int x = (bits.d - 1001 < 0) + (bits.i - 123456790 < 0) * 2 + (-bits.d < 0) * 4 + (bits.g - 2 < 0) * 8; (x == promoted_bits(&bits)) * x;"
+assert 7 "struct S { unsigned a : 3; unsigned long e : 3; unsigned b : 32; } s; s.a = 7; s.e = 7; s.b = 7; int x = 0; switch (s.a - 8) { case 0 - 1: x = 4; } (s.e - 8 < 0) + (sizeof(s.a + 0) == 4) * 2 + (s.b - 8 < 0) * 8 + x;"
+assert 1 "$struct_bit_fields int check_header(struct Header *h); struct Header h = {4, 5, 6, 1500}; check_header(&h);"
+assert 1 "$struct_bit_fields int check_header(struct Header *h); struct Header h; h.version = 4; h.length = 5; h.type = 6; h.total = 1500; check_header(&h);"
+assert 1 "$struct_bit_fields int check_bits(struct Bits *b); struct Bits b = {1, -3, 300000, 1000, -12345678901, 3, 1, 104, 123456789}; check_bits(&b);"
+assert 1 "$struct_bit_fields int check_bits(struct Bits *b); struct Bits b; b.a = 1; b.b = -3; b.c = 300000; b.d = 1000; b.e = -12345678901; b.f = 3; b.g = 5; b.h = 104; b.i = 123456789; check_bits(&b);"
+assert 1 "$struct_bit_fields int check_bits(struct Bits *b); struct Bits b = {1, -3, 300000, 1000, -12345678901, 3, 1, 104, 123456789}; int f() { return check_bits(&b); } f();"
+assert 1 "$struct_bit_fields int check_bits(struct Bits *b); int f() { static struct Bits b = {.i = 123456789, .h = 104, .a = 1, -3, 300000, 1000, -12345678901, 3, 1}; return check_bits(&b); } f();"
+assert 1 "$struct_bit_fields extern struct Bits bits; void set_bits(struct Bits *b); set_bits(&bits); (bits.b == 7) * (bits.c == -5) * (bits.d == 3) * (bits.f == -2) * (bits.i == 42) * (bits.a == 1) * (bits.h == 104) * (bits.g == 1);"
+assert 1 "$struct_bit_fields struct Bits b; ((b.b = 17) == -15) * ((b.d = 1025) == 1) * ((b.g = 2) == 1);"
+assert 3 "$struct_bit_fields struct { unsigned x : 3; unsigned y : 3; } s; s.x = 5; s.y = 6; s.x = s.x + s.y; s.x;"
+assert 6 "struct Node { int value; struct Node *next; }; struct Node c = {3, 0}; struct Node b = {2, &c}; struct Node a = {1, &b}; int sum(struct Node *n) { int s = 0; while (n != 0) { s = s + n->value; n = n->next; } return s; } sum(&a);"
+assert 3 "struct Node { int value; struct Node *next; }; struct Node c = {3, 0}; struct Node b = {2, &c}; struct Node a = {1, &b}; a.next->next->value;"
+assert 7 "struct Tree; struct Tree *left(struct Tree *t); struct Tree { int v; struct Tree *l; struct Tree *r; }; struct Tree *left(struct Tree *t) { return t->l; } struct Tree x = {7, 0, 0}; struct Tree y = {1, &x, 0}; left(&y)->v;"
+assert 2 "struct A; struct B { struct A *a; }; struct A { int x; int y; }; struct A a = {1, 2}; struct B b = {&a}; b.a->y;"
+assert 2 "struct A; struct A *p; struct A { int x; int y; }; struct A a = {1, 2}; p = &a; (p + 1 - 1)->y;"
+assert 8 "struct Buffer { long length; int data[]; }; struct Buffer b; (long)(&b + 1) - (long)&b;"
+assert 8 "struct Buffer { int length; double data[]; }; struct Buffer b; (long)(&b + 1) - (long)&b;"
+assert 9 "struct Buffer { long length; int data[]; }; int storage[10]; struct Buffer *b = (struct Buffer *)storage; b->length = 3; b->data[0] = 4; b->data[2] = 5; b->data[0] + b->data[2] + storage[4] - 5 + b->length - 3;"
+assert 4 "int a[] = {1, 2, 3, 4}; (long)(&a + 1) - (long)&a - 12;"
+assert 5 "char s[] = \"abc\"; int f() { static int t[] = {5, 6, 7}; return t[2]; } (long)(&s + 1) - (long)&s - 1 + f() - 7 + 0 * s[0] + 2;"
+assert 6 "int f() { char c; _Alignas(64) char buf[3]; long x; buf[2] = 5; x = (long)buf; return (x - x / 64 * 64 == 0) + buf[2]; } f();"
+assert 5 "char c; _Alignas(32) int g = 3; _Alignas(64) long h; int f() { long x = (long)&g; long y = (long)&h; return (x - x / 32 * 32 == 0) + (y - y / 64 * 64 == 0) + g; } f();"
+assert 14 "int f() { _Alignas(128) char a[5]; _Alignas(64) struct { int x; } s; char b; long x = (long)a; long y = (long)&s; a[4] = 3; s.x = 4; b = 5; return (x - x / 128 * 128 == 0) + (y - y / 64 * 64 == 0) + a[4] + s.x + b; } f();"
+assert 48 "struct S { char c; _Alignas(16) int x; }; struct S s; (long)&s.x - (long)&s + (long)(&s + 1) - (long)&s;"
+assert 21 "_Alignof(char) + _Alignof(long) + _Alignof(struct { char c; double d; }) + _Alignof(int[3]);"
+assert 2 "_Alignas(long) char c; _Alignas(8) _Alignas(16) short s; _Alignas(0) int i; ((long)&s - (long)&s / 16 * 16 == 0) + ((long)&c - (long)&c / 8 * 8 == 0);"
+assert 5 "_Alignas(16) int x; extern int x; char c; extern _Alignas(32) int y; int y = 3; extern int y; ((long)&x - (long)&x / 16 * 16 == 0) + ((long)&y - (long)&y / 32 * 32 == 0) + y + x;"
+assert 100 "int run_in_threads(int (*f)(int), int count); _Thread_local int count; int bump(int n) { int i; for (i = 0; i < n * 10; i = i + 1) count = count + 1; return count; } run_in_threads(bump, 4) + count;"
+assert 4 "int run_in_threads(int (*f)(int), int count); __thread long total = 3; static _Thread_local int hidden; int f(int n) { static __thread int calls; calls = calls + 1; hidden = hidden + n; total = total + n; return calls * 100 + hidden + total; } f(1) + run_in_threads(f, 3) - 412 - 15 + total + hidden;"
+assert 30 "int run_in_threads(int (*f)(int), int count); extern _Thread_local int thread_tag; int tag(int n) { thread_tag = thread_tag + n; return thread_tag; } thread_tag = 100; run_in_threads(tag, 4) + thread_tag - 100;"
+assert 7 "_Thread_local int rip = 3; static __thread int al = 4; rip + al;"
+assert 11 "int run_in_threads(int (*f)(int), int count); struct P { int x; int y; }; _Thread_local struct P p = {1, 2}; _Thread_local int a[3]; int f(int n) { p.y = p.y + n; a[2] = a[2] + p.y; return a[2] + p.x; } run_in_threads(f, 2) + p.y;"
+assert 24 "int f(int n) { int a[n]; int i; for (i = 0; i < n; i = i + 1) a[i] = i; return a[n - 1] + sizeof(a); } f(5);"
+assert 94 "int f(int n, int m) { int x = 7; int a[n][m]; int i; int j; for (i = 0; i < n; i = i + 1) for (j = 0; j < m; j = j + 1) a[i][j] = i * 10 + j; return a[2][3] + sizeof a + sizeof a[1] + x; } f(3, 4);"
+assert 29 "sizeof(int) + sizeof(char) + sizeof(long *) + sizeof(struct { char c; double d; });"
+assert 10 "int a[3]; char s[] = \"hello\"; int f() { int b[sizeof(long)]; return sizeof b - sizeof a - sizeof s - sizeof(a[0]); } f();"
+assert 3 "int f(int n) { int i; int s = 0; for (i = 0; i < 100000; i = i + 1) { int a[n]; a[n - 1] = i; s = a[n - 1]; } return s - 99999 + 3; } f(1000);"
+assert 3 "int f(int n) { int i = 0; int s = 0; while (1) { int a[n]; a[0] = i; i = i + 1; if (i == 100000) break; { char b[n]; b[1] = 1; s = s + b[1]; if (s > 100000) break; } } return (s == 99999) + 2; } f(1000);"
+assert 5 "int f(int n) { long p; long q; { int a[n]; p = (long)a; } { int b[n * 2]; q = (long)b; } return (p - q == n * 4) + 4; } f(16);"
+assert 24 "int f(int n) { int a[n][n]; int (*p)[n] = a; p[2][1] = 4; return a[2][1] + (long)(p + 1) - (long)p; } f(5);"
+assert 168 "int f(int n, int m) { return sizeof(int[n][m]) + sizeof(char[n + 1]) * 100; } f(3, 2);"
+assert 58 "int f(int n) { long s = sizeof(int (*)[n]); int k = 2; return s + sizeof(double[k][n]) + k; } f(3);"
+assert 132 "int f(int n, int k) { int s = 0; again: { int a[n]; a[0] = k; s = s + a[0]; k = k - 1; if (k) goto again; } return s; } f(1000000, 200);"
+assert 7 "int f(int n) { { int a[n]; a[0] = 1; goto out; } out: return n; } int g(int n) { int i; for (i = 0; i < 100000; i = i + 1) f(n); return 7; } g(1000);"
+assert 5 "int f(int n) { int a[n]; a[0] = 1; int k = 100000; lbl: k = k - 1; int b[n]; b[0] = 2; if (k) goto lbl; return a[0] + b[0] + 2; } f(300);"
+assert 14 "int f(int n) { int s = 0; int a[n]; a[0] = 4; switch (n) { case 3: { int b[n]; b[2] = 5; s = b[2]; } default: s = s + a[0]; } return s; } int g(int n) { int x = 5; return f(n) + x; } g(3);"
+assert 28 "int add3(int a, int b, char *p) { p[0] = 9; return a + b + p[0]; } add3(1, 2, __builtin_alloca(16)) + add3(3, 4, __builtin_alloca(100)) + (long)__builtin_alloca(1) - (long)__builtin_alloca(1) - 16;"
+assert 122 "_Generic(1, int: 2, double: 3, default: 4) + _Generic(1.0, float: 10, double: 20) + _Generic(1.0f, float: 100, default: 0);"
+assert 31 "const int c = 1; int a[3]; char s; _Generic(c, int: 1, default: 0) + _Generic(a, int *: 2, default: 0) + _Generic(s, char: 4, int: 0) + _Generic(\"abc\", char *: 8, default: 0) + _Generic(&c, const int *: 16, int *: 0);"
+assert 7 "_Alignas(16) int x; _Alignas(64) char buf[4]; int *p = &x; _Generic(&x, int *: 1, default: 0) + _Generic(buf, char *: 2, default: 0) + _Generic(&buf, char (*)[4]: 4, default: 0);"
+assert 11 "double twice_d(double x) { return x * 2; } float twice_f(float x) { return x * 3; } int x; long l; x = _Generic(2.0f, double: twice_d, float: twice_f)(2.0f); _Generic(x, long: l, default: x) = x + 5; x;"
+assert 7 "int f(int x) { return x; } long l; _Generic(f, int (*)(int): 1, default: 2) + _Generic(l, long: 2, unsigned long: 0) + _Generic((char)1 + 1, int: 4, char: 0);"
+assert 15 "char c; signed char sc; long l; long long ll; _Generic(c, signed char: 0, char: 1) + _Generic(sc, char: 0, signed char: 2) + _Generic(l, long long: 0, long: 4) + _Generic(ll, long: 0, long long: 8);"
+assert 7 "long l; unsigned long ul; _Generic(l + (long long)1, long long: 1, default: 0) + _Generic((long long)1 + ul, unsigned long long: 2, default: 0) + _Generic(ul + (unsigned long long)1, unsigned long long: 4, default: 0);"
+assert 3 "signed char s[] = \"ab\"; long long ll = 0 - 1; unsigned long long u = ll; (s[0] == 97) + (u > 0) + (ll < 0) - (sizeof ll != 8);"
+many_locals=""
+sum_of_locals=""
+for i in $(seq 0 299); do
+  many_locals+="int v$i = $i; "
+  sum_of_locals+="v$i + "
+done
+# 0 + 1 + ... + 299 = 44850, of which the exit status keeps the lowest 8 bits
+assert 50 "$many_locals abs(0) + ${sum_of_locals}0;"
+assert 50 "int f() { $many_locals return abs(0) + ${sum_of_locals}0; } f();"
+assert_stdout "1 1.50 2 2.50" "print_doubles(1, 1.5, 2, 2.5f);"
+assert_stdout "3 5.00 4 0.50" "double add_doubles(double a, double b); print_doubles(3, add_doubles(2.25, 2.75), 4, 0.5);"
 assert_stdout "foo" "print_foo();"
+assert_stdout "2.50" "printf(\"%.2f\\n\", ((double[]){1.5, 2.5})[1]);"
+assert_stdout "1 2.50 three" "printf(\"%d %.2f %s\\n\", 1, 2.5, \"th\" \"ree\");"
 assert_stdout "3" "print_sum(1, 2);"
+assert_stdout "1 2 3.50 4 5.5" "char *format_string(); long l = 2; float f = 5.5f; printf(format_string(), 1, l, 3.5, 4, f);"
+assert_stdout "1 2 3.50 4 5.5" "char *format_string(); long l = 2; 1 + (2 * (3 + printf(format_string(), 1, l, 3.5, 4, 5.5f)));"
+assert_stdout "1 1.50 2 2.50
+15" "print_sum(1, print_doubles(1, 1.5, 2, 2.5));"
+assert_stdout "1 2 3.50 4 5.5" "char *format_string(); void print_log(char *format, ...) { va_list ap; va_start(ap, format); vprintf(format, ap); va_end(ap); } long l = 2; print_log(format_string(), 1, l, 3.5, 4, 5.5f);"
 
 rm -f $assets
 
@@ -0,0 +1,3 @@
+double add_doubles(double a, double b) {
+    return a + b;
+}
@@ -0,0 +1,3 @@
+int apply(int (*f)(int, int), int a, int b) {
+    return f(a, b);
+}
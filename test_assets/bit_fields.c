@@ -0,0 +1,50 @@
+struct Header {
+    unsigned version : 4;
+    unsigned length : 4;
+    unsigned type : 8;
+    unsigned short total;
+};
+
+struct Bits {
+    char a;
+    int b : 5;
+    int c : 20;
+    unsigned d : 10;
+    long e : 40;
+    short f : 3;
+    _Bool g : 1;
+    int : 0;
+    char h;
+    unsigned : 7;
+    unsigned i : 30;
+};
+
+struct Header header = {4, 5, 6, 1500};
+struct Bits bits = {1, -3, 300000, 1000, -12345678901, 3, 1, 'h', 123456789};
+
+int sizeof_bits(void) {
+    return sizeof(struct Bits);
+}
+
+// the fields are expected to hold the values of the global
+int check_bits(struct Bits *b) {
+    return b->a == 1 && b->b == -3 && b->c == 300000 && b->d == 1000 && b->e == -12345678901
+        && b->f == 3 && b->g == 1 && b->h == 'h' && b->i == 123456789;
+}
+
+int check_header(struct Header *h) {
+    return h->version == 4 && h->length == 5 && h->type == 6 && h->total == 1500;
+}
+
+void set_bits(struct Bits *b) {
+    b->b = 7;
+    b->c = -5;
+    b->d = 3;
+    b->f = -2;
+    b->i = 42;
+}
+
+// unsigned bit-fields narrower than int are promoted to int
+int promoted_bits(struct Bits *b) {
+    return (b->d - 1001 < 0) + (b->i - 123456790 < 0) * 2 + (-b->d < 0) * 4 + (b->g - 2 < 0) * 8;
+}
@@ -0,0 +1,3 @@
+long call_many(long (*f)(long, long, long, long, long, long, long, long)) {
+    return f(1, 2, 3, 4, 5, 6, 7, 8);
+}
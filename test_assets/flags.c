@@ -0,0 +1,15 @@
+#include <stdbool.h>
+
+bool flags[4] = {true, false, true, true};
+
+bool is_odd(int x) {
+    return x & 1;
+}
+
+int count_flags(bool *f, int n) {
+    int count = 0;
+    for (int i = 0; i < n; i++) {
+        count += f[i];
+    }
+    return count;
+}
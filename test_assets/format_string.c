@@ -0,0 +1,3 @@
+char *format_string() {
+    return "%d %ld %.2f %d %.1f\n";
+}
@@ -0,0 +1,13 @@
+struct Mixed {
+    char c;
+    int i;
+    double d;
+    short s;
+    char *p;
+};
+
+struct Mixed mixed = {1, 2, 3.5, 4, "five"};
+
+long sum_mixed(struct Mixed *m) {
+    return m->c + m->i + (long)(m->d * 2) + m->s + m->p[0];
+}
@@ -0,0 +1 @@
+int primes[5] = {2, 3, 5, 7, 11};
@@ -0,0 +1,5 @@
+#include <stdio.h>
+
+int print_doubles(int i, double a, int j, double b) {
+    return printf("%d %.2f %d %.2f\n", i, a, j, b);
+}
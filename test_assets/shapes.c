@@ -0,0 +1,153 @@
+struct C1 {
+    char c;
+};
+
+struct II {
+    int a;
+    int b;
+};
+
+struct LL {
+    long a;
+    long b;
+};
+
+struct DD {
+    double a;
+    double b;
+};
+
+struct FFF {
+    float a;
+    float b;
+    float c;
+};
+
+struct ID {
+    int i;
+    double d;
+};
+
+struct C3 {
+    char c[3];
+};
+
+struct L3 {
+    long a[3];
+};
+
+struct Mix {
+    char c;
+    short s;
+    int i;
+    long l;
+};
+
+struct FI {
+    float f;
+    int i;
+    double d;
+};
+
+struct C1 make_c1(char c) {
+    struct C1 s = {c};
+    return s;
+}
+
+struct II make_ii(int a, int b) {
+    struct II s = {a, b};
+    return s;
+}
+
+struct LL make_ll(long a, long b) {
+    struct LL s = {a, b};
+    return s;
+}
+
+struct DD make_dd(double a, double b) {
+    struct DD s = {a, b};
+    return s;
+}
+
+struct FFF make_fff(double a, double b, double c) {
+    struct FFF s = {a, b, c};
+    return s;
+}
+
+struct ID make_id(int i, double d) {
+    struct ID s = {i, d};
+    return s;
+}
+
+struct C3 make_c3(char a, char b, char c) {
+    struct C3 s = {{a, b, c}};
+    return s;
+}
+
+struct L3 make_l3(long a, long b, long c) {
+    struct L3 s = {{a, b, c}};
+    return s;
+}
+
+struct Mix make_mix(char c, short s, int i, long l) {
+    struct Mix m = {c, s, i, l};
+    return m;
+}
+
+struct FI make_fi(double f, int i, double d) {
+    struct FI s = {f, i, d};
+    return s;
+}
+
+long sum_c1(struct C1 s) { return s.c; }
+long sum_ii(struct II s) { return s.a + s.b; }
+long sum_ll(struct LL s) { return s.a + s.b; }
+double sum_dd(struct DD s) { return s.a + s.b; }
+double sum_fff(struct FFF s) { return s.a + s.b + s.c; }
+double sum_id(struct ID s) { return s.i + s.d; }
+long sum_c3(struct C3 s) { return s.c[0] + s.c[1] + s.c[2]; }
+long sum_l3(struct L3 s) { return s.a[0] + s.a[1] + s.a[2]; }
+long sum_mix(struct Mix m) { return m.c + m.s + m.i + m.l; }
+double sum_fi(struct FI s) { return s.f + s.i + s.d; }
+
+// the structs no longer fit in the registers left and go on the stack
+long sum_spilled(long a, long b, long c, long d, long e, struct LL x, struct II y, struct L3 z) {
+    return a + b + c + d + e + x.a * x.b + y.a * y.b + z.a[0] * z.a[1] * z.a[2];
+}
+
+double sum_doubles_spilled(double a, double b, double c, double d, double e, double f, double g, struct DD x, double h, struct ID y) {
+    return a + b + c + d + e + f + g + x.a * x.b + h + y.i * y.d;
+}
+
+// callbacks are compiled by us and called from here
+long call_make_ll(struct LL (*make)(long, long)) {
+    struct LL s = make(3, 4);
+    return s.a * 10 + s.b;
+}
+
+long call_make_l3(struct L3 (*make)(long, long, long)) {
+    struct L3 s = make(1, 2, 3);
+    return s.a[0] * 100 + s.a[1] * 10 + s.a[2];
+}
+
+double call_make_fi(struct FI (*make)(float, int, double)) {
+    struct FI s = make(1.5f, 2, 3.25);
+    return s.f + s.i + s.d;
+}
+
+long call_sum_mix(long (*sum)(struct Mix)) {
+    struct Mix m = {1, 2, 3, 4};
+    return sum(m);
+}
+
+long call_sum_c3(long (*sum)(struct C3)) {
+    struct C3 s = {{5, 6, 7}};
+    return sum(s);
+}
+
+long call_sum_spilled(long (*sum)(long, long, long, long, long, struct LL, struct II, struct L3)) {
+    struct LL x = {2, 3};
+    struct II y = {4, 5};
+    struct L3 z = {{1, 2, 3}};
+    return sum(1, 2, 3, 4, 5, x, y, z);
+}
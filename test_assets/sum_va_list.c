@@ -0,0 +1,9 @@
+#include <stdarg.h>
+
+int sum_va_list(int n, va_list ap) {
+    int sum = 0;
+    for (int i = 0; i < n; i++) {
+        sum += va_arg(ap, int);
+    }
+    return sum;
+}
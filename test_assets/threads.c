@@ -0,0 +1,32 @@
+#include <pthread.h>
+
+_Thread_local int thread_tag = 5;
+
+struct Job {
+    int (*f)(int);
+    int arg;
+    int result;
+};
+
+static void *run_job(void *arg) {
+    struct Job *job = arg;
+    job->result = job->f(job->arg);
+    return 0;
+}
+
+// runs f(1) ... f(count) in threads of their own and sums up their results
+int run_in_threads(int (*f)(int), int count) {
+    pthread_t threads[8];
+    struct Job jobs[8];
+    int sum = 0;
+    for (int i = 0; i < count; i++) {
+        jobs[i].f = f;
+        jobs[i].arg = i + 1;
+        pthread_create(&threads[i], 0, run_job, &jobs[i]);
+    }
+    for (int i = 0; i < count; i++) {
+        pthread_join(threads[i], 0);
+        sum += jobs[i].result;
+    }
+    return sum;
+}